@@ -4,16 +4,29 @@ use syn::Error as SynError;
 /// Custom error type for ring buffer macro
 #[derive(Debug)]
 pub enum Error {
-    NotAStruct(Span),
+    EnumNotSupported(Span),
+    UnionNotSupported(Span),
     NotNamedFields(Span),
     MissingDataField(Span),
+    MissingRingField(Span, String),
     InvalidDataFieldType(Span),
+    ArrayLengthNotLiteral(Span),
+    MissingCapacity(Span),
+    ConflictingCapacity(Span, usize, usize),
+    MissingRingCapacity(Span),
+    MissingBookkeepingField(Span, String),
+    GenericSmokeTests(Span),
+    ConstNewExtraField(Span),
     Syn(SynError),
 }
 
 impl Error {
-    pub fn not_a_struct(span: Span) -> Self {
-        Error::NotAStruct(span)
+    pub fn enum_not_supported(span: Span) -> Self {
+        Error::EnumNotSupported(span)
+    }
+
+    pub fn union_not_supported(span: Span) -> Self {
+        Error::UnionNotSupported(span)
     }
 
     pub fn not_named_fields(span: Span) -> Self {
@@ -24,15 +37,54 @@ impl Error {
         Error::MissingDataField(span)
     }
 
+    pub fn missing_ring_field(span: Span, name: &syn::Ident) -> Self {
+        Error::MissingRingField(span, name.to_string())
+    }
+
     pub fn invalid_data_field_type(span: Span) -> Self {
         Error::InvalidDataFieldType(span)
     }
 
+    pub fn array_length_not_literal(span: Span) -> Self {
+        Error::ArrayLengthNotLiteral(span)
+    }
+
+    pub fn missing_capacity(span: Span) -> Self {
+        Error::MissingCapacity(span)
+    }
+
+    pub fn conflicting_capacity(span: Span, capacity: usize, len: usize) -> Self {
+        Error::ConflictingCapacity(span, capacity, len)
+    }
+
+    pub fn missing_ring_capacity(span: Span) -> Self {
+        Error::MissingRingCapacity(span)
+    }
+
+    pub fn missing_bookkeeping_field(span: Span, name: &syn::Ident) -> Self {
+        Error::MissingBookkeepingField(span, name.to_string())
+    }
+
+    pub fn generic_smoke_tests(span: Span) -> Self {
+        Error::GenericSmokeTests(span)
+    }
+
+    pub fn const_new_extra_field(span: Span) -> Self {
+        Error::ConstNewExtraField(span)
+    }
+
     pub fn to_compile_error(&self) -> proc_macro2::TokenStream {
         let error = match self {
-            Error::NotAStruct(span) => {
-                SynError::new(*span, "ring_buffer can only be applied to structs")
-            }
+            Error::EnumNotSupported(span) => SynError::new(
+                *span,
+                "ring_buffer cannot be applied to enums; apply it to a struct \
+                 with a `data: Vec<T>` field and hold that struct in the variant instead",
+            ),
+            Error::UnionNotSupported(span) => SynError::new(
+                *span,
+                "ring_buffer cannot be applied to unions; apply it to a struct \
+                 with a `data: Vec<T>` field instead",
+            ),
             Error::NotNamedFields(span) => SynError::new(
                 *span,
                 "ring_buffer only works with structs with named fields",
@@ -41,9 +93,48 @@ impl Error {
                 *span,
                 "ring_buffer requires a field named 'data' of type Vec<T>",
             ),
+            Error::MissingRingField(span, name) => SynError::new(
+                *span,
+                format!(
+                    "ring_buffer requires a field named '{}' of type Vec<T>",
+                    name
+                ),
+            ),
             Error::InvalidDataFieldType(span) => {
                 SynError::new(*span, "data field must be of type Vec<T>")
             }
+            Error::ArrayLengthNotLiteral(span) => SynError::new(
+                *span,
+                "array length must be an integer literal to use it as the ring_buffer capacity",
+            ),
+            Error::MissingCapacity(span) => SynError::new(
+                *span,
+                "expected ring_buffer capacity, or a `data: [T; N]` field to infer it from",
+            ),
+            Error::ConflictingCapacity(span, capacity, len) => SynError::new(
+                *span,
+                format!(
+                    "array length `{}` conflicts with the ring_buffer capacity `{}`",
+                    len, capacity
+                ),
+            ),
+            Error::MissingRingCapacity(span) => SynError::new(
+                *span,
+                "derive(RingBuffer) requires a #[ring_capacity(...)] attribute",
+            ),
+            Error::MissingBookkeepingField(span, name) => SynError::new(
+                *span,
+                format!("derive(RingBuffer) requires a field named '{}'", name),
+            ),
+            Error::GenericSmokeTests(span) => SynError::new(
+                *span,
+                "the `test` flag requires a struct without type or const parameters",
+            ),
+            Error::ConstNewExtraField(span) => SynError::new(
+                *span,
+                "`const_new` cannot initialise fields other than the ring buffer's own, \
+                 since `Default::default()` is not callable in a const fn",
+            ),
             Error::Syn(err) => return err.to_compile_error(),
         };
         error.to_compile_error()
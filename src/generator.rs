@@ -1,100 +1,2345 @@
-use crate::error::Result;
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Data, DeriveInput, Fields, Type};
+use crate::error::{Error, Result};
+use crate::parser::{Ring, RingBufferArgs};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Fields, GenericParam, Generics, TraitBoundModifier, Type, TypeParamBound,
+    TypePath, Visibility, WherePredicate,
+};
 
 /// Add required fields to the struct
-pub fn add_fields(input: &mut DeriveInput) -> Result<()> {
-    if let Data::Struct(data_struct) = &mut input.data {
-        if let Fields::Named(fields) = &mut data_struct.fields {
-            let capacity_field: syn::Field = syn::parse_quote! { capacity: usize };
-            let head_field: syn::Field = syn::parse_quote! { head: usize };
-            let tail_field: syn::Field = syn::parse_quote! { tail: usize };
-            let size_field: syn::Field = syn::parse_quote! { size: usize };
+pub fn add_fields(input: &mut DeriveInput, rings: &[Ring], args: &RingBufferArgs) -> Result<()> {
+    let span = input.ident.span();
+    let fields = match &mut input.data {
+        Data::Struct(data_struct) => match &mut data_struct.fields {
+            Fields::Named(fields) => fields,
+            _ => return Err(Error::not_named_fields(span)),
+        },
+        Data::Enum(_) => return Err(Error::enum_not_supported(span)),
+        Data::Union(_) => return Err(Error::union_not_supported(span)),
+    };
 
-            fields.named.push(capacity_field);
-            fields.named.push(head_field);
-            fields.named.push(tail_field);
-            fields.named.push(size_field);
+    // An array-typed storage field only declares the element type and capacity
+    for field in fields.named.iter_mut() {
+        let ring = rings
+            .iter()
+            .find(|ring| field.ident.as_ref() == Some(&ring.field));
+        if let (Some(ring), Type::Array(_)) = (ring, &field.ty) {
+            let element_type = &ring.element_type;
+            field.ty = syn::parse_quote! { Vec<#element_type> };
+        }
+    }
+
+    for ring in rings {
+        let Ring {
+            capacity_field,
+            head_field,
+            tail_field,
+            size_field,
+            ..
+        } = ring;
+
+        // Allowed so a library using only a few methods stays clean under `deny(warnings)`
+        let index_type = index_type(args);
+        let capacity_field: syn::Field = syn::parse_quote! {
+            #[allow(dead_code)]
+            #capacity_field: #index_type
+        };
+        let head_field: syn::Field = syn::parse_quote! {
+            #[allow(dead_code)]
+            #head_field: #index_type
+        };
+        let tail_field: syn::Field = syn::parse_quote! {
+            #[allow(dead_code)]
+            #tail_field: #index_type
+        };
+        let size_field: syn::Field = syn::parse_quote! {
+            #[allow(dead_code)]
+            #size_field: #index_type
+        };
+
+        fields.named.push(capacity_field);
+        fields.named.push(head_field);
+        fields.named.push(tail_field);
+        fields.named.push(size_field);
+
+        if args.overwrite {
+            let Ring {
+                element_type,
+                on_overflow_field,
+                ..
+            } = ring;
+            let on_overflow_field: syn::Field = syn::parse_quote! {
+                #[allow(dead_code)]
+                #on_overflow_field: Option<Box<dyn FnMut(&#element_type) + Send>>
+            };
+            fields.named.push(on_overflow_field);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the struct already declares the fields `add_fields` would add, for the derive form
+pub fn check_fields(input: &DeriveInput, rings: &[Ring], args: &RingBufferArgs) -> Result<()> {
+    let span = input.ident.span();
+    let fields = match &input.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields) => fields,
+            _ => return Err(Error::not_named_fields(span)),
+        },
+        Data::Enum(_) => return Err(Error::enum_not_supported(span)),
+        Data::Union(_) => return Err(Error::union_not_supported(span)),
+    };
+
+    for ring in rings {
+        // Array storage is only rewritten to `Vec<T>` by the attribute form
+        let storage = fields
+            .named
+            .iter()
+            .find(|f| f.ident.as_ref() == Some(&ring.field));
+        if let Some(field) = storage.filter(|f| matches!(f.ty, Type::Array(_))) {
+            return Err(Error::invalid_data_field_type(field.ty.span()));
+        }
+
+        let mut required = vec![
+            &ring.capacity_field,
+            &ring.head_field,
+            &ring.tail_field,
+            &ring.size_field,
+        ];
+        if args.overwrite {
+            required.push(&ring.on_overflow_field);
+        }
+
+        for name in required {
+            if !fields.named.iter().any(|f| f.ident.as_ref() == Some(name)) {
+                return Err(Error::missing_bookkeeping_field(span, name));
+            }
         }
     }
 
     Ok(())
 }
 
+/// Generate a `#[cfg(test)]` module of smoke tests for the `test` flag
+///
+/// Sample items come from `T::default()`, so the element type must implement `Default`.
+pub fn generate_smoke_tests(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> Result<TokenStream> {
+    if !args.test {
+        return Ok(quote! {});
+    }
+
+    // The tests construct the buffer directly, so only lifetimes can be left to inference
+    let not_lifetime = |param: &&GenericParam| !matches!(param, GenericParam::Lifetime(_));
+    if let Some(param) = input.generics.params.iter().find(not_lifetime) {
+        return Err(Error::generic_smoke_tests(param.span()));
+    }
+
+    let struct_name = &input.ident;
+    let module = format_ident!("{}_smoke_tests", struct_name);
+
+    let tests = rings.iter().map(|ring| {
+        let element_type = &ring.element_type;
+        let sample = ring.method("sample");
+        let empty = ring.method("empty");
+        let full = ring.method("full");
+        let wraparound = ring.method("wraparound");
+        let enqueue = ring.method("enqueue");
+        let dequeue = ring.method("dequeue");
+        let is_empty = ring.method("is_empty");
+        let is_full = ring.method("is_full");
+        let len = ring.method("len");
+        let capacity = ring.method("capacity");
+
+        // With `overwrite`, enqueueing into a full buffer evicts instead of failing unless
+        // there is nothing to evict, and with `on_full = "grow"` it fails only once the
+        // capacity can grow no further
+        let enqueue_when_full = if args.overwrite {
+            quote! {
+                if cap == 0 {
+                    assert!(buf.#enqueue(#sample()).is_err());
+                } else {
+                    assert!(buf.#enqueue(#sample()).is_ok());
+                    assert_eq!(buf.#len(), cap);
+                }
+            }
+        } else if args.grow {
+            quote! {
+                if buf.#enqueue(#sample()).is_ok() {
+                    assert!(buf.#capacity() > cap);
+                    assert_eq!(buf.#len(), cap + 1);
+                }
+            }
+        } else {
+            quote! {
+                assert!(buf.#enqueue(#sample()).is_err());
+            }
+        };
+
+        quote! {
+            fn #sample() -> #element_type {
+                <#element_type as ::core::default::Default>::default()
+            }
+
+            #[test]
+            fn #empty() {
+                let mut buf = #struct_name::new();
+                assert!(buf.#is_empty());
+                assert_eq!(buf.#len(), 0);
+                assert!(buf.#dequeue().is_none());
+            }
+
+            #[test]
+            fn #full() {
+                let mut buf = #struct_name::new();
+                let cap = buf.#capacity();
+                for _ in 0..cap {
+                    assert!(buf.#enqueue(#sample()).is_ok());
+                }
+                assert!(buf.#is_full());
+                assert_eq!(buf.#len(), cap);
+                #enqueue_when_full
+            }
+
+            #[test]
+            fn #wraparound() {
+                let mut buf = #struct_name::new();
+                let cap = buf.#capacity();
+                for _ in 0..cap {
+                    assert!(buf.#enqueue(#sample()).is_ok());
+                }
+                for _ in 0..2 * cap {
+                    assert!(buf.#dequeue().is_some());
+                    assert!(buf.#enqueue(#sample()).is_ok());
+                    assert_eq!(buf.#len(), cap);
+                }
+                for _ in 0..cap {
+                    assert!(buf.#dequeue().is_some());
+                }
+                assert!(buf.#is_empty());
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod #module {
+            use super::*;
+
+            #(#tests)*
+        }
+    })
+}
+
 /// Generate the implementation block for the ring buffer
-pub fn generate_impl(input: &DeriveInput, element_type: &Type, capacity: usize) -> TokenStream {
+pub fn generate_impl(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> Result<TokenStream> {
     let struct_name = &input.ident;
-    let vis = &input.vis;
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let vis = method_vis(input, args);
 
-    // Build where clause for Clone bound on element type
-    let clone_bound = quote! { where #element_type: Clone };
+    // With `error_enum`, enqueue reports failures through a dedicated enum
+    let error_enum_name = enqueue_error_name(input, args);
+    let error_enum = if args.error_enum {
+        generate_error_enum(vis, &error_enum_name)
+    } else {
+        quote! {}
+    };
 
-    quote! {
+    // Borrowed read-only view returned by `snapshot`
+    let view_name = view_name(input, args);
+    let view = generate_view(vis, &view_name);
+
+    // Returned by the fallible constructors and `try_set_capacity`
+    let capacity_error_name = capacity_error_name(input, args);
+    let capacity_error = generate_capacity_error(vis, &capacity_error_name);
+
+    let field_inits = ring_field_inits(rings, args, None);
+    let user_field_inits = user_field_inits(input, rings, args)?;
+
+    let (constness, new_doc) = if args.const_new {
+        (
+            quote! { const },
+            "Create an empty buffer; storage is allocated by the first `enqueue`.",
+        )
+    } else {
+        (
+            quote! {},
+            "Create an empty buffer with storage preallocated for its capacity.",
+        )
+    };
+
+    let (size_items, size_guard) = generate_size_checks(input, rings, args);
+
+    let ring_methods = rings.iter().map(|ring| {
+        // `try_with_capacity` allocates this ring's storage itself, fallibly
+        let unallocated_inits = ring_field_inits(rings, args, Some(ring));
+        let unallocated = quote! {
+            Self {
+                #(#unallocated_inits)*
+                #(#user_field_inits)*
+            }
+        };
+        generate_ring_methods(input, ring, args, &unallocated)
+    });
+
+    let hash_impl = if args.hash {
+        generate_hash_impl(input, rings, args)
+    } else {
+        quote! {}
+    };
+
+    let display_impl = if args.display {
+        generate_display_impl(input, rings, args)
+    } else {
+        quote! {}
+    };
+
+    // Ordering must agree with equality, so `ord` implies `eq`
+    let eq_impls = if args.eq || args.ord {
+        generate_eq_impls(input, rings, args)
+    } else {
+        quote! {}
+    };
+
+    let ord_impls = if args.ord {
+        generate_ord_impls(input, rings, args)
+    } else {
+        quote! {}
+    };
+
+    let sync_wrapper = if args.sync {
+        generate_sync_wrapper(input, rings, args)
+    } else {
+        quote! {}
+    };
+
+    // Only a single `data` ring has an unambiguous array conversion
+    let from_array_impl = match rings {
+        [ring] if args.rings[0].name.is_none() => generate_from_array_impl(input, ring),
+        _ => quote! {},
+    };
+
+    Ok(quote! {
+        #error_enum
+
+        #capacity_error
+
+        #view
+
+        #hash_impl
+
+        #display_impl
+
+        #eq_impls
+
+        #ord_impls
+
+        #sync_wrapper
+
+        #from_array_impl
+
+        #size_items
+
+        // Callers typically use a handful of the generated methods
+        #[allow(dead_code)]
         impl #impl_generics #struct_name #ty_generics #where_clause {
-            #vis fn new() -> Self {
+            #[doc = #new_doc]
+            #vis #constness fn new() -> Self {
+                #size_guard
                 Self {
-                    data: Vec::with_capacity(#capacity),
-                    capacity: #capacity,
-                    head: 0,
-                    tail: 0,
-                    size: 0,
+                    #(#field_inits)*
+                    #(#user_field_inits)*
                 }
             }
 
-            #vis fn enqueue(&mut self, item: #element_type) -> Result<(), #element_type> {
-                if self.is_full() {
-                    return Err(item);
+            #(#ring_methods)*
+        }
+    })
+}
+
+/// Initialisers for every ring's storage and bookkeeping fields, leaving the storage of
+/// `unallocated` empty
+fn ring_field_inits(
+    rings: &[Ring],
+    args: &RingBufferArgs,
+    unallocated: Option<&Ring>,
+) -> Vec<TokenStream> {
+    rings
+        .iter()
+        .map(|ring| {
+            let Ring {
+                field,
+                capacity,
+                capacity_field,
+                head_field,
+                tail_field,
+                size_field,
+                on_overflow_field,
+                ..
+            } = ring;
+
+            let on_overflow_init = if args.overwrite {
+                quote! { #on_overflow_field: None, }
+            } else {
+                quote! {}
+            };
+
+            // `Vec::with_capacity` is not const, so `const_new` defers the allocation to `enqueue`
+            let storage_init = if args.const_new || unallocated.is_some_and(|r| r.field == *field) {
+                quote! { Vec::new() }
+            } else {
+                quote! { Vec::with_capacity(#capacity) }
+            };
+
+            // Unsuffixed so it also initialises a narrower `index` type
+            let capacity_lit = Literal::usize_unsuffixed(*capacity);
+
+            quote! {
+                #field: #storage_init,
+                #capacity_field: #capacity_lit,
+                #head_field: 0,
+                #tail_field: 0,
+                #size_field: 0,
+                #on_overflow_init
+            }
+        })
+        .collect()
+}
+
+/// The struct's own fields other than ring storage and bookkeeping, as `(name, type)`
+fn user_fields<'a>(
+    input: &'a DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> Vec<(&'a Ident, &'a Type)> {
+    let fields = match &input.data {
+        Data::Struct(data_struct) => &data_struct.fields,
+        _ => return Vec::new(),
+    };
+
+    let is_generated = |ident: &Ident| {
+        rings.iter().any(|ring| {
+            ident == &ring.field
+                || ident == &ring.capacity_field
+                || ident == &ring.head_field
+                || ident == &ring.tail_field
+                || ident == &ring.size_field
+                || (args.overwrite && ident == &ring.on_overflow_field)
+        })
+    };
+
+    fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref().map(|ident| (ident, &field.ty)))
+        .filter(|(ident, _)| !is_generated(ident))
+        .collect()
+}
+
+/// `Default::default()` initialisers for the user fields, so `new()` works with extra fields
+/// of types implementing `Default`
+fn user_field_inits(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> Result<Vec<TokenStream>> {
+    user_fields(input, rings, args)
+        .into_iter()
+        .map(|(ident, ty)| {
+            if args.const_new {
+                return Err(Error::const_new_extra_field(ident.span()));
+            }
+
+            // Spanned at the field type so a missing `Default` impl is reported there
+            Ok(quote_spanned! {ty.span()=>
+                #ident: <#ty as ::core::default::Default>::default(),
+            })
+        })
+        .collect()
+}
+
+/// Generate the methods operating on a single ring
+///
+/// `unallocated` builds the struct without allocating this ring's storage.
+fn generate_ring_methods(
+    input: &DeriveInput,
+    ring: &Ring,
+    args: &RingBufferArgs,
+    unallocated: &TokenStream,
+) -> TokenStream {
+    let vis = method_vis(input, args);
+    let Ring {
+        field: data,
+        element_type,
+        capacity,
+        capacity_field,
+        head_field,
+        tail_field,
+        size_field,
+        on_overflow_field: on_overflow,
+        ..
+    } = ring;
+
+    let from_slice = ring.method("from_slice");
+    let from_iter_truncating = ring.method("from_iter_truncating");
+    let try_from_iter = ring.method("try_from_iter");
+    let with_contents = ring.method("with_contents");
+    let try_with_capacity = ring.method("try_with_capacity");
+    let capacity_error_name = capacity_error_name(input, args);
+    let enqueue = ring.method("enqueue");
+    let saturating_enqueue = ring.method("saturating_enqueue");
+    let enqueue_ref = ring.method("enqueue_ref");
+    let enqueue_overwrite_oldest = ring.method("enqueue_overwrite_oldest");
+    let try_extend = ring.method("try_extend");
+    let insert = ring.method("insert");
+    let dequeue = ring.method("dequeue");
+    let dequeue_if = ring.method("dequeue_if");
+    let dequeue_into = ring.method("dequeue_into");
+    let try_dequeue = ring.method("try_dequeue");
+    let remove = ring.method("remove");
+    let take_where = ring.method("take_where");
+    let append = ring.method("append");
+    let merge = ring.method("merge");
+    let peek_n = ring.method("peek_n");
+    let get = ring.method("get");
+    let peek_mut = ring.method("peek_mut");
+    let peek_pair = ring.method("peek_pair");
+    let position_of = ring.method("position_of");
+    let count_where = ring.method("count_where");
+    let count_matching = ring.method("count_matching");
+    let fold = ring.method("fold");
+    let replace_front = ring.method("replace_front");
+    let most_recent = ring.method("most_recent");
+    let to_vec = ring.method("to_vec");
+    let into_vec = ring.method("into_vec");
+    let clone_contents_into = ring.method("clone_contents_into");
+    let swap = ring.method("swap");
+    let windows = ring.method("windows");
+    let chunks = ring.method("chunks");
+    let is_full = ring.method("is_full");
+    let is_empty = ring.method("is_empty");
+    let len = ring.method("len");
+    let capacity_method = ring.method("capacity");
+    let occupancy = ring.method("occupancy");
+    let is_at_least_half_full = ring.method("is_at_least_half_full");
+    let as_slices = ring.method("as_slices");
+    let snapshot = ring.method("snapshot");
+    let view_name = view_name(input, args);
+    let split_index = ring.method("split_index");
+    let is_contiguous = ring.method("is_contiguous");
+    let physical_head = ring.method("physical_head");
+    let physical_tail = ring.method("physical_tail");
+    let logical_to_physical = ring.method("logical_to_physical");
+    let iter_mut = ring.method("iter_mut");
+    let drain_rev = ring.method("drain_rev");
+    let reversed_iter = ring.method("reversed_iter");
+    let make_contiguous = ring.method("make_contiguous");
+    let truncate = ring.method("truncate");
+    let resize = ring.method("resize");
+    let try_set_capacity = ring.method("try_set_capacity");
+    let extract_if = ring.method("extract_if");
+    let drain_filter = ring.method("drain_filter");
+    let clear = ring.method("clear");
+    let clear_and_shrink = ring.method("clear_and_shrink");
+    let clear_with = ring.method("clear_with");
+    let reset_with = ring.method("reset_with");
+    let clone_into = ring.method("clone_into");
+
+    // Build where clause for Clone bound on element type, unless the struct already declares it
+    let clone_predicate = clone_predicate(input, ring);
+    let clone_bound = quote! { where #clone_predicate };
+
+    // Bookkeeping fields are read as `usize` and cast back to the `index` type on writes, so
+    // all arithmetic happens in `usize`. Values never exceed the capacity, which is checked to
+    // fit the type, so the casts back cannot truncate.
+    let index_type = index_type(args);
+    let cap = read_index(quote! { self }, capacity_field, args);
+    let head = read_index(quote! { self }, head_field, args);
+    let tail = read_index(quote! { self }, tail_field, args);
+    let size = read_index(quote! { self }, size_field, args);
+
+    // Every storage position is computed through `wrap_index`
+    let wrap_index = ring.method("wrap_index");
+    let next_tail = quote! { self.#wrap_index(#tail + 1) };
+    let next_head = quote! { self.#wrap_index(#head + 1) };
+
+    // A narrower `index` type also bounds capacities chosen at runtime
+    let (resize_guard, capacity_guard, set_capacity_guard) = match narrow_index_type(args) {
+        Some(index_type) => (
+            quote! {
+                assert!(
+                    #index_type::try_from(new_cap).is_ok(),
+                    "capacity does not fit the index type"
+                );
+            },
+            quote! {
+                if #index_type::try_from(capacity).is_err() {
+                    return Err(#capacity_error_name::Invalid { capacity });
+                }
+            },
+            quote! {
+                || #index_type::try_from(new_cap).is_err()
+            },
+        ),
+        None => (quote! {}, quote! {}, quote! {}),
+    };
+
+    // With `on_full = "grow"`, a runtime capacity must not start beyond `max` either
+    let max_capacity_guard = match args.max_capacity {
+        Some(max) => quote! { || capacity > #max },
+        _ => quote! {},
+    };
+
+    // Checked after every mutation in debug builds only
+    let invariants = quote! {
+        debug_assert!(#size <= #cap);
+        debug_assert!(#head < #cap || #cap == 0);
+        debug_assert!(self.#data.len() <= #cap);
+    };
+
+    // Storage is unallocated with `const_new` and after `clear_and_shrink`, so it is sized
+    // exactly on first use instead of growing through `Vec::push`
+    let reserve_storage = quote! {
+        if self.#data.capacity() == 0 {
+            self.#data.reserve_exact(#cap);
+        }
+    };
+
+    let enqueue_result = enqueue_result_type(input, ring, args);
+    let enqueue_doc = if args.overwrite {
+        "Add an item at the back, evicting the oldest item if the buffer is full.\n\n\
+         Returns `Ok(Some(evicted))` when an item was evicted, and `Err(item)` only for a \
+         zero-capacity buffer."
+    } else if args.grow {
+        "Add an item at the back, doubling the capacity first if the buffer is full.\n\n\
+         Growth is capped at `max`; the rejected item is returned in the error only once the \
+         buffer is full at that capacity, or if the larger storage cannot be allocated."
+    } else if args.error_enum {
+        "Add an item at the back.\n\n\
+         Returns `Err(Full(item))` with the rejected item if the buffer is full."
+    } else {
+        "Add an item at the back.\n\n\
+         Returns `Err(item)` with the rejected item if the buffer is full."
+    };
+    let full_error = if args.error_enum {
+        let error_enum_name = enqueue_error_name(input, args);
+        quote! { #error_enum_name::Full(item) }
+    } else {
+        quote! { item }
+    };
+
+    // With `overwrite`, a full buffer evicts its oldest element and hands it back
+    let (on_full, enqueued, overwrite_methods, bulk_overwrite) = if args.overwrite {
+        let set_on_overflow = ring.method("set_on_overflow");
+        (
+            quote! {
+                // A zero-capacity buffer has no slot to overwrite
+                if #cap == 0 {
+                    return Err(#full_error);
+                }
+
+                let evicted = ::core::mem::replace(&mut self.#data[#tail], item);
+                self.#tail_field = (#next_tail) as #index_type;
+                self.#head_field = self.#tail_field;
+                #invariants
+
+                if let Some(on_overflow) = self.#on_overflow.as_mut() {
+                    on_overflow(&evicted);
+                }
+                return Ok(Some(evicted));
+            },
+            quote! { Ok(None) },
+            quote! {
+                /// Register a callback that receives each item evicted by an overwriting enqueue.
+                #vis fn #set_on_overflow(&mut self, f: Box<dyn FnMut(&#element_type) + Send>) {
+                    self.#on_overflow = Some(f);
+                }
+            },
+            // `enqueue` already evicts, and reports evictions to the callback
+            quote! {
+                let _ = self.#enqueue(item);
+            },
+        )
+    } else {
+        // With `on_full = "grow"`, a full buffer reallocates at double the capacity instead
+        let on_full = if args.grow {
+            let grown = match (args.max_capacity, narrow_index_type(args)) {
+                (Some(max), _) => quote! { #cap.saturating_mul(2).min(#max) },
+                (None, Some(index_type)) => {
+                    quote! { #cap.saturating_mul(2).min(#index_type::MAX as usize) }
+                }
+                (None, None) => quote! { #cap.saturating_mul(2) },
+            };
+            quote! {
+                let grown = #grown;
+                if grown <= #cap {
+                    return Err(#full_error);
                 }
 
-                if self.data.len() <= self.tail {
-                    self.data.push(item);
+                // Unwrap the ring so the new slots follow the newest item
+                self.#make_contiguous();
+                if self.#data.try_reserve_exact(grown - self.#data.len()).is_err() {
+                    return Err(#full_error);
+                }
+                self.#capacity_field = grown as #index_type;
+                self.#tail_field = self.#size_field;
+                #invariants
+            }
+        } else {
+            quote! { return Err(#full_error); }
+        };
+        (
+            on_full,
+            quote! { Ok(()) },
+            quote! {},
+            quote! {
+                if self.#is_full() && #cap > 0 {
+                    self.#data[#tail] = item;
+                    self.#tail_field = (#next_tail) as #index_type;
+                    self.#head_field = self.#tail_field;
+                    #invariants
                 } else {
-                    self.data[self.tail] = item;
+                    let _ = self.#enqueue(item);
                 }
+            },
+        )
+    };
 
-                self.tail = (self.tail + 1) % self.capacity;
-                self.size += 1;
-                Ok(())
+    // With `numeric`, add arithmetic helpers for summable element types
+    let numeric_methods = if args.numeric {
+        let sum = ring.method("sum");
+        quote! {
+            /// Sum the live items.
+            #vis fn #sum(&self) -> #element_type
+            where
+                #element_type: ::core::iter::Sum + Copy,
+            {
+                (0..#size)
+                    .map(|i| self.#data[self.#wrap_index(#head + i)])
+                    .sum()
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // With `spare_slots`, expose uninitialized storage for direct writes; opt-in so that
+    // default buffers contain no `unsafe` code
+    let spare_slot_methods = if args.spare_slots {
+        let spare_slots_mut = ring.method("spare_slots_mut");
+        let commit = ring.method("commit");
+        quote! {
+            /// Borrow the free slots after the newest item as uninitialized memory.
+            ///
+            /// The live items are first moved to the front of storage, so the returned slice
+            /// holds every free slot. Write to a prefix of it, then call `commit` with its
+            /// length to make those items live.
+            #vis fn #spare_slots_mut(&mut self) -> &mut [::core::mem::MaybeUninit<#element_type>] {
+                self.#make_contiguous();
+                // Stale slots are initialized values, so drop them to make them spare capacity
+                self.#data.truncate(#size);
+                let free = #cap - #size;
+                self.#data.reserve_exact(free);
+                &mut self.#data.spare_capacity_mut()[..free]
+            }
+
+            /// Make the first `n` slots returned by `spare_slots_mut` live, as newest items.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `n` exceeds the free slots after the newest item.
+            ///
+            /// # Safety
+            ///
+            /// The first `n` slots of `spare_slots_mut` must have been initialized, with no
+            /// other call on the buffer in between.
+            #vis unsafe fn #commit(&mut self, n: usize) {
+                let len = self.#data.len();
+                assert!(
+                    #tail == len % #cap.max(1) && n <= #cap - len,
+                    "commit beyond the spare slots"
+                );
+
+                // SAFETY: the caller initialized these slots, which lie within capacity
+                unsafe { self.#data.set_len(len + n) };
+                self.#size_field = (#size + n) as #index_type;
+                self.#tail_field = (if len + n == #cap { 0 } else { len + n }) as #index_type;
+                #invariants
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // With `vecdeque_api`, `VecDeque` names for the queue operations ease migration
+    let vecdeque_methods = if args.vecdeque_api {
+        let push_back = ring.method("push_back");
+        let pop_front = ring.method("pop_front");
+        quote! {
+            /// Alias for `enqueue`.
+            #[inline]
+            #vis fn #push_back(&mut self, item: #element_type) -> #enqueue_result {
+                self.#enqueue(item)
             }
 
-            #vis fn dequeue(&mut self) -> Option<#element_type>
+            /// Alias for `dequeue`.
+            #[inline]
+            #vis fn #pop_front(&mut self) -> Option<#element_type>
                 #clone_bound
             {
-                if self.is_empty() {
-                    return None;
+                self.#dequeue()
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // With `unchecked`, an enqueue that trusts the caller to have checked for space
+    let unchecked_methods = if args.unchecked {
+        let enqueue_unchecked = ring.method("enqueue_unchecked");
+        quote! {
+            /// Add an item at the back without checking whether the buffer is full.
+            ///
+            /// # Safety
+            ///
+            /// The buffer must not be full. Enqueueing into a full buffer breaks the ring's
+            /// bookkeeping; debug builds catch it with an assertion.
+            #[inline]
+            #vis unsafe fn #enqueue_unchecked(&mut self, item: #element_type) {
+                debug_assert!(!self.#is_full(), "enqueue_unchecked on a full buffer");
+
+                if self.#data.len() <= #tail {
+                    #reserve_storage
+                    self.#data.push(item);
+                } else {
+                    self.#data[#tail] = item;
                 }
 
-                let item = self.data[self.head].clone();
-                self.head = (self.head + 1) % self.capacity;
-                self.size -= 1;
+                self.#tail_field = (#next_tail) as #index_type;
+                self.#size_field = (#size + 1) as #index_type;
+                #invariants
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        /// Build a buffer from a slice, cloning items in order.
+        ///
+        /// Items beyond the capacity are ignored, as `enqueue` would reject them.
+        #vis fn #from_slice(items: &[#element_type]) -> Self
+            #clone_bound
+        {
+            Self::#from_iter_truncating(items.iter().cloned())
+        }
 
-                Some(item)
+        /// Build a buffer from an iterator, keeping only the first `capacity` items.
+        #vis fn #from_iter_truncating<I>(iter: I) -> Self
+        where
+            I: IntoIterator<Item = #element_type>,
+        {
+            let mut buffer = Self::new();
+            for item in iter.into_iter().take(#capacity) {
+                let _ = buffer.#enqueue(item);
             }
+            buffer
+        }
 
-            #vis fn is_full(&self) -> bool {
-                self.size == self.capacity
+        /// Build a buffer holding `items`, oldest first.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `items` holds more than `capacity` items; use `try_from_iter` to handle
+        /// that case instead.
+        #vis fn #with_contents(items: Vec<#element_type>) -> Self {
+            assert!(
+                items.len() <= #capacity,
+                "with_contents: {} items exceed capacity {}",
+                items.len(),
+                #capacity
+            );
+            Self::#from_iter_truncating(items)
+        }
+
+        /// Create an empty buffer with a runtime capacity instead of the attribute's.
+        ///
+        /// Returns an error instead of panicking if `capacity` is 0 or cannot be allocated.
+        #vis fn #try_with_capacity(capacity: usize) -> Result<Self, #capacity_error_name> {
+            if capacity == 0 #max_capacity_guard {
+                return Err(#capacity_error_name::Invalid { capacity });
             }
+            #capacity_guard
 
-            #vis fn is_empty(&self) -> bool {
-                self.size == 0
+            // Checked before anything is allocated, and then allocated only once
+            let mut buffer = #unallocated;
+            if buffer.#data.try_reserve_exact(capacity).is_err() {
+                return Err(#capacity_error_name::Invalid { capacity });
             }
+            buffer.#capacity_field = capacity as #index_type;
+            Ok(buffer)
+        }
 
-            #vis fn len(&self) -> usize {
-                self.size
+        /// Build a buffer from an iterator that must fit within the capacity.
+        ///
+        /// If it yields more than `capacity` items, the iterator is run to the end and an
+        /// error reports how many items were attempted.
+        #vis fn #try_from_iter<I>(iter: I) -> Result<Self, #capacity_error_name>
+        where
+            I: IntoIterator<Item = #element_type>,
+        {
+            let mut iter = iter.into_iter();
+            let buffer = Self::#from_iter_truncating(iter.by_ref());
+            let extra = iter.count();
+            if extra > 0 {
+                return Err(#capacity_error_name::Overflow {
+                    capacity: #capacity,
+                    attempted: #capacity + extra,
+                });
             }
+            Ok(buffer)
+        }
 
-            #vis fn capacity(&self) -> usize {
-                self.capacity
+        #[doc = #enqueue_doc]
+        #[inline]
+        #vis fn #enqueue(&mut self, item: #element_type) -> #enqueue_result {
+            if self.#is_full() {
+                #on_full
+            }
+
+            if self.#data.len() <= #tail {
+                #reserve_storage
+                self.#data.push(item);
+            } else {
+                self.#data[#tail] = item;
+            }
+
+            self.#tail_field = (#next_tail) as #index_type;
+            self.#size_field = (#size + 1) as #index_type;
+            #invariants
+            #enqueued
+        }
+
+        /// Add a clone of `item` at the back.
+        ///
+        /// Returns `Err(())` if the buffer is full; the caller still holds the original.
+        // The signature is fixed by the element type, e.g. `&Cow<str>` or `&Vec<u8>`
+        #[allow(clippy::ptr_arg, clippy::result_unit_err)]
+        #vis fn #enqueue_ref(&mut self, item: &#element_type) -> Result<(), ()>
+            #clone_bound
+        {
+            self.#enqueue(<#element_type as Clone>::clone(item))
+                .map(|_| ())
+                .map_err(|_| ())
+        }
+
+        /// Add an item at the back, dropping it if the buffer is full.
+        ///
+        /// Returns `true` if the item was stored.
+        #[inline]
+        #vis fn #saturating_enqueue(&mut self, item: #element_type) -> bool {
+            self.#enqueue(item).is_ok()
+        }
+
+        /// Add every item, evicting the oldest as needed.
+        ///
+        /// Afterwards the buffer holds the last `capacity` items of the old contents followed by `items`.
+        #vis fn #enqueue_overwrite_oldest<I>(&mut self, items: I)
+        where
+            I: IntoIterator<Item = #element_type>,
+        {
+            for item in items {
+                #bulk_overwrite
+            }
+        }
+
+        /// Add items from an iterator until it runs out or the buffer is full.
+        ///
+        /// Nothing is evicted or grown. If the buffer fills first, returns the number of items
+        /// added and the rest of the iterator. The rest is only known to be empty when its
+        /// `size_hint` says so, so an iterator that exactly fills the buffer returns `Ok` only
+        /// if it reports an exact size.
+        #vis fn #try_extend<I>(&mut self, iter: I) -> Result<(), (usize, I::IntoIter)>
+        where
+            I: IntoIterator<Item = #element_type>,
+        {
+            let mut iter = iter.into_iter();
+            let mut inserted = 0;
+            while !self.#is_full() {
+                match iter.next() {
+                    Some(item) => {
+                        let _ = self.#enqueue(item);
+                        inserted += 1;
+                    }
+                    None => return Ok(()),
+                }
+            }
+
+            if iter.size_hint().1 == Some(0) {
+                return Ok(());
+            }
+            Err((inserted, iter))
+        }
+
+        /// Insert an item at logical position `index`, shifting later items toward the back.
+        ///
+        /// Returns `Err(item)` with the rejected item if the buffer is full.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `index` is greater than `len()`.
+        #vis fn #insert(&mut self, index: usize, item: #element_type) -> Result<(), #element_type> {
+            assert!(
+                index <= #size,
+                "insert index {} out of range for length {}",
+                index,
+                #size
+            );
+            if self.#is_full() {
+                return Err(item);
+            }
+
+            if self.#data.len() <= #tail {
+                #reserve_storage
+                self.#data.push(item);
+            } else {
+                self.#data[#tail] = item;
+            }
+
+            self.#tail_field = (#next_tail) as #index_type;
+            self.#size_field = (#size + 1) as #index_type;
+
+            // Walk the new item back from the tail, across the wrap if needed
+            for i in (index..#size - 1).rev() {
+                self.#swap(i, i + 1);
+            }
+            #invariants
+
+            Ok(())
+        }
+
+        /// Remove and return the oldest item, or `None` if the buffer is empty.
+        #[inline]
+        #vis fn #dequeue(&mut self) -> Option<#element_type>
+            #clone_bound
+        {
+            if self.#is_empty() {
+                return None;
+            }
+
+            let item = <#element_type as Clone>::clone(&self.#data[#head]);
+            self.#head_field = (#next_head) as #index_type;
+            self.#size_field = (#size - 1) as #index_type;
+            #invariants
+
+            Some(item)
+        }
+
+        /// Remove up to `out.len()` of the oldest items, cloning them into `out` oldest first.
+        ///
+        /// Returns the number written; slots of `out` past that are left untouched.
+        #vis fn #dequeue_into(&mut self, out: &mut [#element_type]) -> usize
+            #clone_bound
+        {
+            let count = out.len().min(#size);
+            if count == 0 {
+                return 0;
+            }
+
+            for (i, slot) in out[..count].iter_mut().enumerate() {
+                *slot = <#element_type as Clone>::clone(&self.#data[self.#wrap_index(#head + i)]);
+            }
+            self.#head_field = (self.#wrap_index(#head + count)) as #index_type;
+            self.#size_field = (#size - count) as #index_type;
+            #invariants
+
+            count
+        }
+
+        /// Remove and return the oldest item by moving it out, without cloning.
+        ///
+        /// Storage after the item shifts down to close the gap, so this is O(n); prefer
+        /// `dequeue` when `T: Clone` and cloning is cheap.
+        #vis fn #try_dequeue(&mut self) -> Option<#element_type> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            // Removing from a wrapped ring would shift the wrong items
+            if #head + #size > #cap {
+                self.#make_contiguous();
+            }
+
+            let item = self.#data.remove(#head);
+            self.#size_field = (#size - 1) as #index_type;
+            self.#tail_field = (#head + #size) as #index_type;
+            #invariants
+
+            Some(item)
+        }
+
+        /// Remove and return the item at logical position `index`, shifting later items toward
+        /// the front, or `None` if `index` is not less than `len()`.
+        ///
+        /// The item is moved out without cloning; storage after it shifts down, so this is O(n).
+        #vis fn #remove(&mut self, index: usize) -> Option<#element_type> {
+            if index >= #size {
+                return None;
             }
 
-            #vis fn clear(&mut self) {
-                self.head = 0;
-                self.tail = 0;
-                self.size = 0;
+            // Removing from a wrapped ring would shift the wrong items
+            if #head + #size > #cap {
+                self.#make_contiguous();
             }
+
+            let item = self.#data.remove(#head + index);
+            self.#size_field = (#size - 1) as #index_type;
+            self.#tail_field = (#head + #size) as #index_type;
+            #invariants
+
+            Some(item)
+        }
+
+        /// Remove and return the oldest item `pred` accepts, wherever it is in the buffer.
+        ///
+        /// Remaining items keep their order.
+        #vis fn #take_where<F>(&mut self, pred: F) -> Option<#element_type>
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            let index = self.#position_of(pred)?;
+            self.#remove(index)
+        }
+
+        /// Remove and return the oldest item only if `pred` accepts it.
+        #vis fn #dequeue_if<F>(&mut self, pred: F) -> Option<#element_type>
+        where
+            F: FnOnce(&#element_type) -> bool,
+            #clone_predicate
+        {
+            if self.#is_empty() || !pred(&self.#data[#head]) {
+                return None;
+            }
+
+            self.#dequeue()
+        }
+
+        /// Move items from the front of `other` to the back of this buffer, oldest first.
+        ///
+        /// Stops when this buffer is full or `other` is empty, and returns the number moved.
+        #vis fn #append(&mut self, other: &mut Self) -> usize {
+            let count = other.#len().min(#cap - #size);
+            if count == 0 {
+                return 0;
+            }
+
+            other.#make_contiguous();
+            for item in other.#data.drain(..count) {
+                let _ = self.#enqueue(item);
+            }
+
+            // `other` is left contiguous from index 0 and no longer full
+            other.#size_field = (other.#len() - count) as #index_type;
+            other.#head_field = 0;
+            other.#tail_field = other.#size_field;
+            count
+        }
+
+        /// Move as many of `other`'s items as fit, as `append` without the count.
+        ///
+        /// Items that do not fit stay in `other`; nothing in this buffer is overwritten.
+        #vis fn #merge(&mut self, other: &mut Self) {
+            self.#append(other);
+        }
+
+        /// Borrow up to `n` of the oldest items, oldest first.
+        #vis fn #peek_n(&self, n: usize) -> Vec<&#element_type> {
+            (0..n.min(#size))
+                .map(|i| &self.#data[self.#wrap_index(#head + i)])
+                .collect()
+        }
+
+        /// Borrow the item at logical position `index`, where 0 is the oldest.
+        #[inline]
+        #vis fn #get(&self, index: usize) -> Option<&#element_type> {
+            if index >= #size {
+                return None;
+            }
+
+            Some(&self.#data[self.#wrap_index(#head + index)])
+        }
+
+        /// Mutably borrow the oldest item, or `None` if the buffer is empty.
+        #[inline]
+        #vis fn #peek_mut(&mut self) -> Option<&mut #element_type> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            Some(&mut self.#data[#head])
+        }
+
+        /// Borrow the oldest and newest items together, or `None` if the buffer is empty.
+        ///
+        /// With a single item both references point to it.
+        #[inline]
+        #vis fn #peek_pair(&self) -> Option<(&#element_type, &#element_type)> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            let back = self.#wrap_index(#head + #size - 1);
+            Some((&self.#data[#head], &self.#data[back]))
+        }
+
+        /// Logical position of the oldest item `pred` accepts, for use with `get` or `swap`.
+        #vis fn #position_of<F>(&self, mut pred: F) -> Option<usize>
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            (0..#size).find(|&i| pred(&self.#data[self.#wrap_index(#head + i)]))
+        }
+
+        /// Number of live items `pred` accepts.
+        #vis fn #count_where<F>(&self, mut pred: F) -> usize
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            (0..#size)
+                .filter(|&i| pred(&self.#data[self.#wrap_index(#head + i)]))
+                .count()
+        }
+
+        /// Alias for `count_where`.
+        #vis fn #count_matching<F>(&self, pred: F) -> usize
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            self.#count_where(pred)
+        }
+
+        /// Fold the live items, oldest first, without cloning them.
+        #vis fn #fold<B, F>(&self, init: B, mut f: F) -> B
+        where
+            F: FnMut(B, &#element_type) -> B,
+        {
+            (0..#size).fold(init, |acc, i| {
+                f(acc, &self.#data[self.#wrap_index(#head + i)])
+            })
+        }
+
+        /// Replace the oldest item, returning the previous one, or `None` if the buffer is empty.
+        #vis fn #replace_front(&mut self, value: #element_type) -> Option<#element_type> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            Some(::core::mem::replace(&mut self.#data[#head], value))
+        }
+
+        /// Clone the live items into a `Vec`, oldest first.
+        #vis fn #to_vec(&self) -> Vec<#element_type>
+            #clone_bound
+        {
+            self.#most_recent(#size)
+        }
+
+        /// Replace the contents of `out` with clones of the live items, oldest first.
+        ///
+        /// `out` keeps its allocation, growing it only if it is too small.
+        #vis fn #clone_contents_into(&self, out: &mut Vec<#element_type>)
+            #clone_bound
+        {
+            out.clear();
+            let (front, back) = self.#as_slices();
+            out.extend_from_slice(front);
+            out.extend_from_slice(back);
+        }
+
+        /// Consume the buffer and move the live items into a `Vec`, oldest first.
+        #vis fn #into_vec(mut self) -> Vec<#element_type> {
+            self.#make_contiguous();
+            let mut items = ::core::mem::take(&mut self.#data);
+            items.truncate(#size);
+            items
+        }
+
+        /// Clone up to `n` of the newest items, oldest first.
+        #vis fn #most_recent(&self, n: usize) -> Vec<#element_type>
+            #clone_bound
+        {
+            let start = #size - n.min(#size);
+            (start..#size)
+                .map(|i| <#element_type as Clone>::clone(&self.#data[self.#wrap_index(#head + i)]))
+                .collect()
+        }
+
+        /// Swap the items at logical positions `i` and `j`, where 0 is the oldest.
+        ///
+        /// # Panics
+        ///
+        /// Panics if either index is not less than `len()`.
+        #vis fn #swap(&mut self, i: usize, j: usize) {
+            assert!(i < #size, "swap index {} out of range for length {}", i, #size);
+            assert!(j < #size, "swap index {} out of range for length {}", j, #size);
+
+            let phys_i = self.#wrap_index(#head + i);
+            let phys_j = self.#wrap_index(#head + j);
+            self.#data.swap(phys_i, phys_j);
+        }
+
+        /// Iterate over every run of `n` consecutive items, oldest first.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `n` is 0.
+        #vis fn #windows(&self, n: usize) -> impl Iterator<Item = Vec<&#element_type>> + '_ {
+            assert!(n > 0, "window size must be non-zero");
+
+            let count = if n > #size { 0 } else { #size - n + 1 };
+            (0..count).map(move |start| {
+                (start..start + n)
+                    .map(|i| &self.#data[self.#wrap_index(#head + i)])
+                    .collect()
+            })
+        }
+
+        /// Iterate over non-overlapping runs of up to `n` items, oldest first.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `n` is 0.
+        #vis fn #chunks(&self, n: usize) -> impl Iterator<Item = Vec<&#element_type>> + '_ {
+            assert!(n > 0, "chunk size must be non-zero");
+
+            (0..#size).step_by(n).map(move |start| {
+                (start..(start + n).min(#size))
+                    .map(|i| &self.#data[self.#wrap_index(#head + i)])
+                    .collect()
+            })
+        }
+
+        // Storage positions are a head or tail below the capacity plus an offset of at most
+        // the capacity, so a single compare-and-subtract wraps them without a division. The
+        // capacity can change at runtime, so a power-of-two literal cannot become a mask.
+        #[inline(always)]
+        fn #wrap_index(&self, position: usize) -> usize {
+            if position >= #cap {
+                position - #cap
+            } else {
+                position
+            }
+        }
+
+        // The trivial accessors are always inlined so they cost nothing across crates
+        /// Returns `true` if the buffer holds `capacity` items.
+        #[inline(always)]
+        #vis fn #is_full(&self) -> bool {
+            #size == #cap
         }
+
+        /// Returns `true` if the buffer holds no items.
+        #[inline(always)]
+        #vis fn #is_empty(&self) -> bool {
+            #size == 0
+        }
+
+        /// Number of items currently stored.
+        #[inline(always)]
+        #vis fn #len(&self) -> usize {
+            #size
+        }
+
+        /// Maximum number of items the buffer can hold.
+        #[inline(always)]
+        #vis fn #capacity_method(&self) -> usize {
+            #cap
+        }
+
+        /// Fraction of the capacity in use, from 0.0 to 1.0 (1.0 for a zero-capacity buffer).
+        #[inline]
+        #vis fn #occupancy(&self) -> f32 {
+            if #cap == 0 {
+                return 1.0;
+            }
+
+            // Divide in f64 so large capacities keep their precision
+            (#size as f64 / #cap as f64) as f32
+        }
+
+        /// Returns `true` if at least half of the capacity is in use.
+        #vis fn #is_at_least_half_full(&self) -> bool {
+            // Compare against the rounded-up half without multiplying `size`
+            #size >= #cap - #cap / 2
+        }
+
+        /// Borrow the live items as two slices in FIFO order.
+        ///
+        /// The second slice is empty unless the items wrap around the end of storage.
+        #vis fn #as_slices(&self) -> (&[#element_type], &[#element_type]) {
+            let end = #head + #size;
+            if end <= #cap {
+                (&self.#data[#head..end], &[])
+            } else {
+                (&self.#data[#head..], &self.#data[..end - #cap])
+            }
+        }
+
+        /// Borrow a read-only view of the live items.
+        #vis fn #snapshot(&self) -> #view_name<'_, #element_type> {
+            #view_name {
+                data: &self.#data,
+                head: #head,
+                size: #size,
+                capacity: #cap,
+            }
+        }
+
+        /// Logical index at which storage wraps around, where `as_slices` splits.
+        ///
+        /// Returns `None` when the live items are already contiguous.
+        #vis fn #split_index(&self) -> Option<usize> {
+            if #head + #size > #cap {
+                Some(#cap - #head)
+            } else {
+                None
+            }
+        }
+
+        /// Returns `true` if the live items occupy one run of storage, as `as_slices` returning
+        /// an empty second slice; an empty buffer is contiguous.
+        #[inline]
+        #vis fn #is_contiguous(&self) -> bool {
+            #head + #size <= #cap
+        }
+
+        /// Storage index of the oldest item, or `None` if the buffer is empty.
+        #vis fn #physical_head(&self) -> Option<usize> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            Some(#head)
+        }
+
+        /// Storage index of the newest item, or `None` if the buffer is empty.
+        #vis fn #physical_tail(&self) -> Option<usize> {
+            if self.#is_empty() {
+                return None;
+            }
+
+            Some(self.#wrap_index(#head + #size - 1))
+        }
+
+        /// Storage index of the item at logical position `index`, or `None` if `index` is not
+        /// less than `len()`.
+        #vis fn #logical_to_physical(&self, index: usize) -> Option<usize> {
+            if index >= #size {
+                return None;
+            }
+
+            Some(self.#wrap_index(#head + index))
+        }
+
+        /// Iterate over the live items from newest to oldest.
+        #vis fn #reversed_iter(&self) -> impl Iterator<Item = &#element_type> + '_ {
+            (0..#size)
+                .rev()
+                .map(move |i| &self.#data[self.#wrap_index(#head + i)])
+        }
+
+        /// Iterate over mutable references to the live items, oldest first.
+        #vis fn #iter_mut(&mut self) -> impl Iterator<Item = &mut #element_type> + '_ {
+            // Split at `head` so the two halves can be borrowed mutably at once
+            let wrapped_len = (#head + #size).saturating_sub(#cap);
+            let front_len = #size - wrapped_len;
+            let (wrapped, front) = self.#data.split_at_mut(#head);
+            front
+                .iter_mut()
+                .take(front_len)
+                .chain(wrapped.iter_mut().take(wrapped_len))
+        }
+
+        /// Remove every item and iterate over them newest first, moving them out without cloning.
+        ///
+        /// The buffer is empty as soon as this returns; items not consumed are dropped with the
+        /// iterator. The allocation is kept.
+        #vis fn #drain_rev(&mut self) -> impl Iterator<Item = #element_type> + '_ {
+            self.#make_contiguous();
+            self.#data.truncate(#size);
+            self.#head_field = 0;
+            self.#tail_field = 0;
+            self.#size_field = 0;
+            #invariants
+
+            self.#data.drain(..).rev()
+        }
+
+        /// Rearrange storage so the live items form one slice in FIFO order, and return it
+        /// mutably.
+        #vis fn #make_contiguous(&mut self) -> &mut [#element_type] {
+            // `data` only wraps once it has grown to full capacity
+            if self.#data.len() == #cap {
+                self.#data.rotate_left(#head);
+            } else {
+                self.#data.drain(..#head);
+            }
+
+            self.#head_field = 0;
+            self.#tail_field = (if #size == #cap { 0 } else { #size }) as #index_type;
+            #invariants
+
+            &mut self.#data[..#size]
+        }
+
+        /// Keep only the `len` oldest items, dropping the rest.
+        ///
+        /// Has no effect if `len` is not less than the current length.
+        #vis fn #truncate(&mut self, len: usize) {
+            if len >= #size {
+                return;
+            }
+
+            // Cut the storage itself so the removed items are dropped now
+            self.#make_contiguous();
+            self.#data.truncate(len);
+            self.#tail_field = len as #index_type;
+            self.#size_field = len as #index_type;
+            #invariants
+        }
+
+        /// Change the capacity, keeping the newest items that still fit.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `new_cap` is 0.
+        #vis fn #resize(&mut self, new_cap: usize)
+            #clone_bound
+        {
+            assert!(new_cap > 0, "capacity must be greater than 0");
+            #resize_guard
+
+            let skip = #size.saturating_sub(new_cap);
+            let mut data = Vec::with_capacity(new_cap);
+            for i in skip..#size {
+                data.push(<#element_type as Clone>::clone(
+                    &self.#data[self.#wrap_index(#head + i)],
+                ));
+            }
+
+            self.#size_field = data.len() as #index_type;
+            self.#data = data;
+            self.#capacity_field = new_cap as #index_type;
+            self.#head_field = 0;
+            self.#tail_field = (if #size == new_cap { 0 } else { #size }) as #index_type;
+            #invariants
+        }
+
+        /// Change the capacity like `resize`, but fail instead of panicking or dropping items.
+        ///
+        /// Returns an error, leaving the buffer unchanged, if `new_cap` is 0 or smaller than
+        /// the current length.
+        #vis fn #try_set_capacity(&mut self, new_cap: usize) -> Result<(), #capacity_error_name>
+            #clone_bound
+        {
+            if new_cap == 0 #set_capacity_guard {
+                return Err(#capacity_error_name::Invalid { capacity: new_cap });
+            }
+            if new_cap < #size {
+                return Err(#capacity_error_name::Overflow {
+                    capacity: new_cap,
+                    attempted: #size,
+                });
+            }
+
+            self.#resize(new_cap);
+            Ok(())
+        }
+
+        /// Remove every item `pred` accepts and return them, oldest first.
+        ///
+        /// Remaining items keep their order.
+        #vis fn #extract_if<F>(&mut self, mut pred: F) -> Vec<#element_type>
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            // Move the live elements out in logical order
+            self.#make_contiguous();
+            let mut items = ::core::mem::take(&mut self.#data);
+            items.truncate(#size);
+
+            let mut removed = Vec::new();
+            let mut kept = Vec::with_capacity(#cap);
+            for item in items {
+                if pred(&item) {
+                    removed.push(item);
+                } else {
+                    kept.push(item);
+                }
+            }
+
+            self.#size_field = kept.len() as #index_type;
+            self.#data = kept;
+            self.#head_field = 0;
+            self.#tail_field = (if #size == #cap { 0 } else { #size }) as #index_type;
+            #invariants
+
+            removed
+        }
+
+        /// Alias for `extract_if`.
+        #vis fn #drain_filter<F>(&mut self, pred: F) -> Vec<#element_type>
+        where
+            F: FnMut(&#element_type) -> bool,
+        {
+            self.#extract_if(pred)
+        }
+
+        /// Remove and drop all items, keeping the allocation.
+        #vis fn #clear(&mut self) {
+            // Drop held items now rather than when their slots are next overwritten
+            self.#data.clear();
+            self.#head_field = 0;
+            self.#tail_field = 0;
+            self.#size_field = 0;
+            #invariants
+        }
+
+        /// Remove every item, passing each to `f` by value, oldest first, keeping the allocation.
+        ///
+        /// Items are moved out without cloning. If `f` panics, the remaining items are dropped
+        /// and the buffer is left empty.
+        #vis fn #clear_with<F>(&mut self, mut f: F)
+        where
+            F: FnMut(#element_type),
+        {
+            self.#make_contiguous();
+            self.#data.truncate(#size);
+            self.#head_field = 0;
+            self.#tail_field = 0;
+            self.#size_field = 0;
+            #invariants
+
+            for item in self.#data.drain(..) {
+                f(item);
+            }
+        }
+
+        /// Remove all items and release the storage allocation.
+        ///
+        /// The next `enqueue` allocates storage for the full capacity again, in one step.
+        #vis fn #clear_and_shrink(&mut self) {
+            self.#clear();
+            self.#data.shrink_to_fit();
+        }
+
+        /// Replace the contents with clones of `items`, reusing the allocation.
+        ///
+        /// Items beyond the capacity are ignored.
+        #vis fn #reset_with(&mut self, items: &[#element_type])
+            #clone_bound
+        {
+            self.#clear();
+            for item in items.iter().take(#cap) {
+                let _ = self.#enqueue(<#element_type as Clone>::clone(item));
+            }
+        }
+
+        /// Replace the contents of `dst` with clones of the live items, oldest first.
+        ///
+        /// `dst` keeps its storage, so repeated snapshots into the same buffer do not allocate.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the two buffers have different capacities.
+        #vis fn #clone_into(&self, dst: &mut Self)
+            #clone_bound
+        {
+            assert!(
+                #cap == dst.#capacity_method(),
+                "clone_into capacity mismatch: {} into {}",
+                #cap,
+                dst.#capacity_method()
+            );
+
+            dst.#clear();
+            for i in 0..#size {
+                let item = &self.#data[self.#wrap_index(#head + i)];
+                let _ = dst.#enqueue(<#element_type as Clone>::clone(item));
+            }
+        }
+
+        #overwrite_methods
+
+        #numeric_methods
+
+        #spare_slot_methods
+
+        #unchecked_methods
+
+        #vecdeque_methods
+    }
+}
+
+/// Type of the bookkeeping fields: the `index` option, or `usize`
+fn index_type(args: &RingBufferArgs) -> TokenStream {
+    match &args.index_type {
+        Some(index_type) => quote! { #index_type },
+        None => quote! { usize },
+    }
+}
+
+/// The `index` type when it is narrower than `usize` and needs conversions
+fn narrow_index_type(args: &RingBufferArgs) -> Option<&Ident> {
+    args.index_type
+        .as_ref()
+        .filter(|index_type| *index_type != "usize")
+}
+
+/// A bookkeeping field of `receiver` read as `usize`
+///
+/// With a narrower `index` type the read converts the field, so the generated arithmetic is
+/// always done in `usize`.
+fn read_index(receiver: TokenStream, field: &Ident, args: &RingBufferArgs) -> TokenStream {
+    match narrow_index_type(args) {
+        Some(_) => quote! { (#receiver.#field as usize) },
+        None => quote! { #receiver.#field },
+    }
+}
+
+/// Return type of a ring's `enqueue`, which depends on `error_enum` and `overwrite`
+fn enqueue_result_type(input: &DeriveInput, ring: &Ring, args: &RingBufferArgs) -> TokenStream {
+    let element_type = &ring.element_type;
+
+    let ok_type = if args.overwrite {
+        quote! { Option<#element_type> }
+    } else {
+        quote! { () }
+    };
+    let error_type = if args.error_enum {
+        let error_enum_name = enqueue_error_name(input, args);
+        quote! { #error_enum_name<#element_type> }
+    } else {
+        quote! { #element_type }
+    };
+
+    quote! { Result<#ok_type, #error_type> }
+}
+
+/// Name of the type returned by `snapshot`: `view_name` if given, otherwise `<Name>View`
+fn view_name(input: &DeriveInput, args: &RingBufferArgs) -> Ident {
+    args.view_name
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}View", input.ident))
+}
+
+/// Name of the error returned by the fallible constructors and `try_set_capacity`:
+/// `capacity_error_name` if given, otherwise `<Name>CapacityError`
+fn capacity_error_name(input: &DeriveInput, args: &RingBufferArgs) -> Ident {
+    args.capacity_error_name
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}CapacityError", input.ident))
+}
+
+/// Name of the error enum emitted with `error_enum`: `enqueue_error_name` if given, otherwise
+/// `<Name>EnqueueError`
+fn enqueue_error_name(input: &DeriveInput, args: &RingBufferArgs) -> Ident {
+    args.enqueue_error_name
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}EnqueueError", input.ident))
+}
+
+/// Name of the wrapper emitted with `sync`: `sync_name` if given, otherwise `Sync<Name>`
+fn sync_name(input: &DeriveInput, args: &RingBufferArgs) -> Ident {
+    args.sync_name
+        .clone()
+        .unwrap_or_else(|| format_ident!("Sync{}", input.ident))
+}
+
+/// Visibility of generated methods: `method_vis` if given, otherwise the struct's own
+fn method_vis<'a>(input: &'a DeriveInput, args: &'a RingBufferArgs) -> &'a Visibility {
+    args.method_vis.as_ref().unwrap_or(&input.vis)
+}
+
+/// The `T: Clone` predicate for methods that clone elements, or nothing when the element
+/// type is a type parameter the struct already bounds by `Clone`
+fn clone_predicate(input: &DeriveInput, ring: &Ring) -> Option<TokenStream> {
+    let element_type = &ring.element_type;
+    if element_type_declares_clone(&input.generics, element_type) {
+        return None;
+    }
+
+    // Span the bound at the struct's own parameter so a missing `Clone` is reported there
+    let span = input
+        .generics
+        .type_params()
+        .find(|tp| matches!(element_type, Type::Path(path) if path.path.is_ident(&tp.ident)))
+        .map_or_else(|| element_type.span(), |tp| tp.ident.span());
+    Some(quote_spanned! {span=> #element_type: Clone, })
+}
+
+/// Whether `ty` is a bare type parameter with a `Clone` bound inline or in the where clause
+fn element_type_declares_clone(generics: &Generics, ty: &Type) -> bool {
+    let param = match ty {
+        Type::Path(TypePath { qself: None, path }) => match path.get_ident() {
+            Some(ident) => ident,
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    let is_clone = |bound: &TypeParamBound| match bound {
+        TypeParamBound::Trait(trait_bound) => {
+            matches!(trait_bound.modifier, TraitBoundModifier::None)
+                && trait_bound
+                    .path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident == "Clone")
+                    .unwrap_or(false)
+        }
+        _ => false,
+    };
+
+    let inline = generics
+        .type_params()
+        .filter(|tp| &tp.ident == param)
+        .any(|tp| tp.bounds.iter().any(is_clone));
+
+    let in_where = generics.where_clause.iter().any(|where_clause| {
+        where_clause
+            .predicates
+            .iter()
+            .any(|predicate| match predicate {
+                WherePredicate::Type(pt) => &pt.bounded_ty == ty && pt.bounds.iter().any(is_clone),
+                _ => false,
+            })
+    });
+
+    inline || in_where
+}
+
+/// Clone the struct generics, requiring every ring's element type to satisfy `bound`
+fn generics_with_bound(input: &DeriveInput, rings: &[Ring], bound: TokenStream) -> Generics {
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ring in rings {
+        let element_type = &ring.element_type;
+        where_clause
+            .predicates
+            .push(syn::parse_quote! { #element_type: #bound });
+    }
+    generics
+}
+
+/// Compile-time checks that each ring's storage can be allocated and, with `max_elem_size`,
+/// that its element type fits
+///
+/// The parser only bounds the capacity itself; `capacity * size_of::<T>()` must also stay
+/// within `isize::MAX` bytes, or `new()` would panic in `Vec::with_capacity`.
+///
+/// Returns free-standing `const _` assertions for a struct without generic parameters.
+/// Otherwise the element type may name a parameter, which a free `const` cannot, so the
+/// assertions are returned as an inline `const` block for `new()`, checked per instantiation.
+fn generate_size_checks(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> (TokenStream, TokenStream) {
+    let assertions = rings.iter().map(|ring| {
+        let element_type = &ring.element_type;
+        let capacity = ring.capacity;
+        let storage_message = format!(
+            "ring_buffer capacity {} times the element size exceeds isize::MAX bytes",
+            capacity
+        );
+        let elem_size_check = args.max_elem_size.map(|max| {
+            let message = format!("element type is larger than max_elem_size ({} bytes)", max);
+            quote_spanned! {element_type.span()=>
+                assert!(::core::mem::size_of::<#element_type>() <= #max, #message);
+            }
+        });
+
+        // Spanned at the element type so a failure points at the oversized type
+        quote_spanned! {element_type.span()=>
+            assert!(
+                match #capacity.checked_mul(::core::mem::size_of::<#element_type>()) {
+                    Some(bytes) => bytes <= isize::MAX as usize,
+                    None => false,
+                },
+                #storage_message
+            );
+            #elem_size_check
+        }
+    });
+
+    if !input.generics.params.is_empty() {
+        (quote! {}, quote! { const { #(#assertions)* } })
+    } else {
+        let items = assertions.map(|assertion| quote! { const _: () = { #assertion }; });
+        (quote! { #(#items)* }, quote! {})
+    }
+}
+
+/// Generate a `Hash` impl over the logical contents, ignoring physical layout, and any user
+/// fields
+fn generate_hash_impl(input: &DeriveInput, rings: &[Ring], args: &RingBufferArgs) -> TokenStream {
+    let struct_name = &input.ident;
+    let generics = generics_with_bound(input, rings, quote! { ::core::hash::Hash });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let hash_rings = rings.iter().map(|ring| {
+        let size = read_index(quote! { self }, &ring.size_field, args);
+        let items = logical_items(ring, quote! { self }, args);
+
+        quote! {
+            ::core::hash::Hash::hash(&#size, state);
+            for item in #items {
+                ::core::hash::Hash::hash(item, state);
+            }
+        }
+    });
+
+    // User fields are part of the value too, so they are hashed after the rings
+    let hash_fields = user_fields(input, rings, args)
+        .into_iter()
+        .map(|(ident, ty)| {
+            quote_spanned! {ty.span()=>
+                ::core::hash::Hash::hash(&self.#ident, state);
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::core::hash::Hash for #struct_name #ty_generics #where_clause {
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #(#hash_rings)*
+                #(#hash_fields)*
+            }
+        }
+    }
+}
+
+/// Generate a `Display` impl listing the live items as `[a, b, c]`, one list per ring
+fn generate_display_impl(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> TokenStream {
+    let struct_name = &input.ident;
+    let generics = generics_with_bound(input, rings, quote! { ::core::fmt::Display });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let display_rings = rings.iter().enumerate().map(|(index, ring)| {
+        let items = logical_items(ring, quote! { self }, args);
+        let separator = if index == 0 { "" } else { " " };
+
+        quote! {
+            f.write_str(#separator)?;
+            f.write_str("[")?;
+            for (i, item) in #items.enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                ::core::fmt::Display::fmt(item, f)?;
+            }
+            f.write_str("]")?;
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #struct_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #(#display_rings)*
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Iterator over references to a ring's live elements in FIFO order
+fn logical_items(ring: &Ring, receiver: TokenStream, args: &RingBufferArgs) -> TokenStream {
+    let data = &ring.field;
+    let wrap_index = ring.method("wrap_index");
+    let head = read_index(receiver.clone(), &ring.head_field, args);
+    let size = read_index(receiver.clone(), &ring.size_field, args);
+
+    quote! {
+        (0..#size).map(|i| &#receiver.#data[#receiver.#wrap_index(#head + i)])
+    }
+}
+
+/// Generate `PartialEq`/`Eq` impls comparing logical contents, ignoring physical layout, and
+/// then any user fields
+fn generate_eq_impls(input: &DeriveInput, rings: &[Ring], args: &RingBufferArgs) -> TokenStream {
+    let struct_name = &input.ident;
+    let partial_eq_generics = generics_with_bound(input, rings, quote! { PartialEq });
+    let (impl_generics, ty_generics, where_clause) = partial_eq_generics.split_for_impl();
+    let eq_generics = generics_with_bound(input, rings, quote! { Eq });
+    let (eq_impl_generics, _, eq_where_clause) = eq_generics.split_for_impl();
+
+    let eq_rings = rings.iter().map(|ring| {
+        let size = &ring.size_field;
+        let ours = logical_items(ring, quote! { self }, args);
+        let theirs = logical_items(ring, quote! { other }, args);
+        quote! {
+            self.#size == other.#size && #ours.eq(#theirs)
+        }
+    });
+
+    let eq_fields = user_fields(input, rings, args)
+        .into_iter()
+        .map(|(ident, ty)| {
+            quote_spanned! {ty.span()=>
+                && self.#ident == other.#ident
+            }
+        });
+
+    quote! {
+        impl #impl_generics PartialEq for #struct_name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                #(#eq_rings)&&* #(#eq_fields)*
+            }
+        }
+
+        impl #eq_impl_generics Eq for #struct_name #ty_generics #eq_where_clause {}
+    }
+}
+
+/// Generate `PartialOrd`/`Ord` impls comparing logical contents lexicographically, and then
+/// any user fields
+fn generate_ord_impls(input: &DeriveInput, rings: &[Ring], args: &RingBufferArgs) -> TokenStream {
+    let struct_name = &input.ident;
+    let partial_ord_generics = generics_with_bound(input, rings, quote! { PartialOrd });
+    let (impl_generics, ty_generics, where_clause) = partial_ord_generics.split_for_impl();
+    let ord_generics = generics_with_bound(input, rings, quote! { Ord });
+    let (ord_impl_generics, _, ord_where_clause) = ord_generics.split_for_impl();
+
+    let partial_cmp_rings = rings.iter().map(|ring| {
+        let ours = logical_items(ring, quote! { self }, args);
+        let theirs = logical_items(ring, quote! { other }, args);
+        quote! {
+            match #ours.partial_cmp(#theirs) {
+                Some(::core::cmp::Ordering::Equal) => {}
+                ordering => return ordering,
+            }
+        }
+    });
+
+    let cmp_rings = rings.iter().map(|ring| {
+        let ours = logical_items(ring, quote! { self }, args);
+        let theirs = logical_items(ring, quote! { other }, args);
+        quote! {
+            match #ours.cmp(#theirs) {
+                ::core::cmp::Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+    });
+
+    // User fields break ties between equal rings, in declaration order
+    let fields = user_fields(input, rings, args);
+    let partial_cmp_fields = fields.iter().map(|(ident, ty)| {
+        quote_spanned! {ty.span()=>
+            match PartialOrd::partial_cmp(&self.#ident, &other.#ident) {
+                Some(::core::cmp::Ordering::Equal) => {}
+                ordering => return ordering,
+            }
+        }
+    });
+    let cmp_fields = fields.iter().map(|(ident, ty)| {
+        quote_spanned! {ty.span()=>
+            match Ord::cmp(&self.#ident, &other.#ident) {
+                ::core::cmp::Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics PartialOrd for #struct_name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                #(#partial_cmp_rings)*
+                #(#partial_cmp_fields)*
+                Some(::core::cmp::Ordering::Equal)
+            }
+        }
+
+        impl #ord_impl_generics Ord for #struct_name #ty_generics #ord_where_clause {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                #(#cmp_rings)*
+                #(#cmp_fields)*
+                ::core::cmp::Ordering::Equal
+            }
+        }
+    }
+}
+
+/// Generate `From<[T; CAPACITY]>`, filling the buffer by moving the array's elements
+fn generate_from_array_impl(input: &DeriveInput, ring: &Ring) -> TokenStream {
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let element_type = &ring.element_type;
+    let capacity = ring.capacity;
+    let enqueue = ring.method("enqueue");
+
+    quote! {
+        impl #impl_generics ::core::convert::From<[#element_type; #capacity]>
+            for #struct_name #ty_generics #where_clause
+        {
+            fn from(items: [#element_type; #capacity]) -> Self {
+                let mut buffer = Self::new();
+                for item in items {
+                    let _ = buffer.#enqueue(item);
+                }
+                buffer
+            }
+        }
+    }
+}
+
+/// Generate `Sync<Name>`, a `Mutex`-backed wrapper that locks around each operation
+fn generate_sync_wrapper(
+    input: &DeriveInput,
+    rings: &[Ring],
+    args: &RingBufferArgs,
+) -> TokenStream {
+    let struct_name = &input.ident;
+    let sync_name = sync_name(input, args);
+    let struct_vis = &input.vis;
+    let vis = method_vis(input, args);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let ring_methods = rings.iter().map(|ring| {
+        let element_type = &ring.element_type;
+        let clone_predicate = clone_predicate(input, ring);
+        let enqueue_result = enqueue_result_type(input, ring, args);
+        let enqueue = ring.method("enqueue");
+        let dequeue = ring.method("dequeue");
+        let len = ring.method("len");
+        let is_empty = ring.method("is_empty");
+
+        quote! {
+            /// Lock the buffer and add an item at the back, as the unsynchronized `enqueue`.
+            #vis fn #enqueue(&self, item: #element_type) -> #enqueue_result {
+                self.lock().#enqueue(item)
+            }
+
+            /// Lock the buffer and remove the oldest item.
+            #vis fn #dequeue(&self) -> Option<#element_type>
+            where
+                #clone_predicate
+            {
+                self.lock().#dequeue()
+            }
+
+            /// Lock the buffer and return the number of items stored.
+            #vis fn #len(&self) -> usize {
+                self.lock().#len()
+            }
+
+            /// Lock the buffer and return `true` if it holds no items.
+            #vis fn #is_empty(&self) -> bool {
+                self.lock().#is_empty()
+            }
+        }
+    });
+
+    quote! {
+        /// A thread-safe wrapper that locks a `Mutex` around each operation.
+        #struct_vis struct #sync_name #impl_generics #where_clause {
+            inner: ::std::sync::Mutex<#struct_name #ty_generics>,
+        }
+
+        impl #impl_generics #sync_name #ty_generics #where_clause {
+            /// Create an empty, unlocked buffer.
+            #vis fn new() -> Self {
+                Self {
+                    inner: ::std::sync::Mutex::new(#struct_name::new()),
+                }
+            }
+
+            /// Lock the buffer for a sequence of operations.
+            ///
+            /// A poisoned lock is recovered, since a panic cannot leave the ring half-updated.
+            #vis fn lock(&self) -> ::std::sync::MutexGuard<'_, #struct_name #ty_generics> {
+                self.inner
+                    .lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+            }
+
+            /// Consume the wrapper and return the buffer.
+            #vis fn into_inner(self) -> #struct_name #ty_generics {
+                self.inner
+                    .into_inner()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner)
+            }
+
+            #(#ring_methods)*
+        }
+    }
+}
+
+/// Generate the read-only view returned by `snapshot`, shared by every ring of the struct
+fn generate_view(vis: &Visibility, view_name: &syn::Ident) -> TokenStream {
+    quote! {
+        /// A read-only view of a ring's live items, returned by `snapshot`.
+        #vis struct #view_name<'a, T> {
+            data: &'a [T],
+            head: usize,
+            size: usize,
+            capacity: usize,
+        }
+
+        impl<'a, T> #view_name<'a, T> {
+            // Positions are below twice the capacity, so one subtraction wraps them
+            #[inline(always)]
+            fn wrap_index(position: usize, capacity: usize) -> usize {
+                if position >= capacity {
+                    position - capacity
+                } else {
+                    position
+                }
+            }
+
+            /// Number of items in the view.
+            #vis fn len(&self) -> usize {
+                self.size
+            }
+
+            /// Returns `true` if the view holds no items.
+            #vis fn is_empty(&self) -> bool {
+                self.size == 0
+            }
+
+            /// Borrow the item at logical position `index`, where 0 is the oldest.
+            #vis fn get(&self, index: usize) -> Option<&'a T> {
+                if index >= self.size {
+                    return None;
+                }
+
+                Some(&self.data[Self::wrap_index(self.head + index, self.capacity)])
+            }
+
+            /// Iterate over the items, oldest first.
+            #vis fn iter(&self) -> impl Iterator<Item = &'a T> + 'a {
+                let data = self.data;
+                let head = self.head;
+                let capacity = self.capacity;
+                (0..self.size).map(move |i| &data[Self::wrap_index(head + i, capacity)])
+            }
+        }
+
+        // Derives would needlessly require `T: Clone`
+        impl<'a, T> ::core::clone::Clone for #view_name<'a, T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<'a, T> ::core::marker::Copy for #view_name<'a, T> {}
+    }
+}
+
+/// Generate the error returned by the fallible constructors and `try_set_capacity`
+fn generate_capacity_error(vis: &Visibility, capacity_error_name: &syn::Ident) -> TokenStream {
+    quote! {
+        /// Error returned by `try_with_capacity`, `try_from_iter` or `try_set_capacity`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis enum #capacity_error_name {
+            /// The capacity is 0, cannot be allocated, or does not fit the index type
+            Invalid {
+                /// The rejected capacity
+                capacity: usize,
+            },
+            /// The items do not fit in the capacity
+            Overflow {
+                /// Capacity of the ring being built or requested
+                capacity: usize,
+                /// Number of items that had to fit
+                attempted: usize,
+            },
+        }
+
+        impl #capacity_error_name {
+            /// The rejected or requested capacity.
+            #vis fn capacity(&self) -> usize {
+                match *self {
+                    Self::Invalid { capacity } | Self::Overflow { capacity, .. } => capacity,
+                }
+            }
+
+            /// Number of items beyond the capacity, or 0 for an invalid capacity.
+            #vis fn overflow(&self) -> usize {
+                match *self {
+                    Self::Invalid { .. } => 0,
+                    Self::Overflow {
+                        capacity,
+                        attempted,
+                    } => attempted.saturating_sub(capacity),
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #capacity_error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match *self {
+                    Self::Invalid { capacity } => {
+                        write!(f, "invalid ring buffer capacity {}", capacity)
+                    }
+                    Self::Overflow {
+                        capacity,
+                        attempted,
+                    } => write!(
+                        f,
+                        "{} items do not fit in a ring buffer of capacity {}",
+                        attempted, capacity
+                    ),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #capacity_error_name {}
+    }
+}
+
+/// Generate the error enum returned by `enqueue` when `error_enum` is set
+fn generate_error_enum(vis: &Visibility, error_enum_name: &syn::Ident) -> TokenStream {
+    quote! {
+        /// Error returned by `enqueue`, carrying the rejected item.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error_enum_name<T> {
+            /// The buffer was at capacity; the rejected item is returned
+            Full(T),
+        }
+
+        impl<T> #error_enum_name<T> {
+            /// Recover the rejected item.
+            #vis fn into_inner(self) -> T {
+                match self {
+                    #error_enum_name::Full(item) => item,
+                }
+            }
+        }
+
+        impl<T> ::core::fmt::Display for #error_enum_name<T> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #error_enum_name::Full(_) => f.write_str("ring buffer is full"),
+                }
+            }
+        }
+
+        impl<T: ::core::fmt::Debug> ::std::error::Error for #error_enum_name<T> {}
     }
 }
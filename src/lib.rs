@@ -19,14 +19,154 @@
 //! ## Generated Methods
 //!
 //! - `new()` - Create empty buffer
+//! - `from_slice(items: &[T])` - Create buffer from the first `capacity` items (requires `T: Clone`)
+//! - `from_iter_truncating(iter)` - Create buffer from the first `capacity` items of an iterator
+//! - `with_contents(items: Vec<T>)` - Create buffer holding `items` (panics beyond `capacity`)
+//! - `try_with_capacity(cap) -> Result<Self, <Name>CapacityError>` - Create buffer with a
+//!   runtime capacity, failing for 0, above `max`, or an unallocatable size
+//! - `try_from_iter(iter) -> Result<Self, <Name>CapacityError>` - Create buffer, failing if the
+//!   iterator yields more than `capacity` items
 //! - `enqueue(item: T) -> Result<(), T>` - Add item (returns `Err(item)` if full)
+//! - `enqueue_ref(&T) -> Result<(), ()>` - Add a clone of a borrowed item (requires `T: Clone`)
+//! - `saturating_enqueue(item: T) -> bool` - Add item, dropping it if full
+//! - `enqueue_overwrite_oldest(items)` - Add every item, evicting the oldest when full
+//! - `try_extend(iter) -> Result<(), (usize, I::IntoIter)>` - Add items until full, returning
+//!   the count added and the rest of the iterator if it did not fit
+//! - `insert(index, item) -> Result<(), T>` - Add item at a logical index, shifting later items
+//!   back (panics if `index > len()`)
 //! - `dequeue() -> Option<T>` - Remove oldest item (requires `T: Clone`)
+//! - `dequeue_into(&mut [T]) -> usize` - Remove the oldest items into a caller's slice,
+//!   returning the count (requires `T: Clone`)
+//! - `try_dequeue() -> Option<T>` - Move the oldest item out without cloning (O(n))
+//! - `remove(index) -> Option<T>` - Move out the item at a logical index, shifting later items
+//!   forward (O(n))
+//! - `take_where(pred) -> Option<T>` - Remove the oldest matching item from anywhere in the buffer
+//! - `dequeue_if(pred) -> Option<T>` - Remove oldest item only if `pred` accepts it
+//! - `append(other) -> usize` - Move items from `other` until full, returning the count moved
+//! - `merge(other)` - Like `append`, leaving whatever does not fit in `other`
+//! - `peek_n(n) -> Vec<&T>` - Borrow up to `n` oldest items without removing them
+//! - `get(index) -> Option<&T>` - Borrow the item at a logical index (0 is the oldest)
+//! - `peek_mut() -> Option<&mut T>` - Mutably borrow the oldest item
+//! - `peek_pair() -> Option<(&T, &T)>` - Borrow the oldest and newest items together
+//! - `position_of(pred) -> Option<usize>` - Logical index of the oldest matching item
+//! - `count_where(pred) -> usize` - Count matching items without allocating
+//!   (`count_matching(pred)` is an alias)
+//! - `fold(init, f) -> B` - Fold borrowed items in FIFO order
+//! - `replace_front(value) -> Option<T>` - Swap out the oldest item, keeping the length
+//! - `to_vec() -> Vec<T>` - Clone all items in FIFO order
+//! - `clone_contents_into(&mut Vec<T>)` - Replace a `Vec`'s contents with clones of all items
+//!   in FIFO order, reusing its allocation
+//! - `into_vec(self) -> Vec<T>` - Move all items out in FIFO order, consuming the buffer
+//! - `most_recent(n) -> Vec<T>` - Clone up to `n` newest items, oldest first
+//! - `swap(i, j)` - Swap elements at logical indices (panics if out of range)
+//! - `windows(n)` - Iterate over overlapping windows of `n` borrowed items
+//! - `chunks(n)` - Iterate over non-overlapping chunks of up to `n` borrowed items
 //! - `is_full()`, `is_empty()`, `len()`, `capacity()`, `clear()`
+//! - `occupancy() -> f32` - Fill fraction in `0.0..=1.0`; `is_at_least_half_full()`
+//! - `reversed_iter() -> impl Iterator<Item = &T>` - References from newest to oldest
+//! - `iter_mut() -> impl Iterator<Item = &mut T>` - Mutable references in FIFO order
+//! - `drain_rev() -> impl Iterator<Item = T>` - Empty the buffer, moving items out newest first
+//! - `as_slices() -> (&[T], &[T])` - Live items as two slices in FIFO order
+//! - `snapshot() -> <Name>View<'_, T>` - Read-only view with `len()`, `get()` and `iter()`
+//! - `split_index() -> Option<usize>` - Logical index where storage wraps, if it does
+//! - `is_contiguous() -> bool` - Whether the live items occupy one run of storage
+//! - `physical_head()`, `physical_tail() -> Option<usize>` - Storage indices of the oldest and
+//!   newest items, for debugging wraparound
+//! - `logical_to_physical(index) -> Option<usize>` - Storage index of a logical index
+//! - `make_contiguous() -> &mut [T]` - Rearrange storage so the live items form one slice
+//! - `truncate(len)` - Keep only the `len` oldest items
+//! - `resize(new_cap)` - Change capacity, keeping the newest items (requires `T: Clone`)
+//! - `try_set_capacity(new_cap) -> Result<(), <Name>CapacityError>` - Like `resize`, but fails
+//!   for 0 or a capacity below `len()`
+//! - `extract_if(pred) -> Vec<T>` - Remove and return matching items in FIFO order
+//!   (`drain_filter(pred)` is an alias)
+//! - `clear_with(f)` - Clear, passing each item to `f` by value in FIFO order
+//! - `clear_and_shrink()` - Clear and release the backing allocation
+//! - `reset_with(items: &[T])` - Clear and refill from the first `capacity` items (requires `T: Clone`)
+//! - `clone_into(&mut dst)` - Replace `dst`'s contents with clones of the live items, reusing
+//!   its allocation (requires `T: Clone` and equal capacities)
+//!
+//! A single-ring buffer also implements `From<[T; CAPACITY]>`, producing a full buffer in
+//! array order.
+//!
+//! ## Inferred Capacity
+//!
+//! Without a capacity, `#[ring_buffer]` reads it from an array-typed field: `data: [T; 8]`
+//! declares a ring of capacity 8, and the field is replaced with `Vec<T>` storage. A given
+//! capacity must agree with the array length.
+//!
+//! ## Multiple Rings
+//!
+//! `#[ring_buffer(inbound = 16, outbound = 8)]` manages the `Vec<T>` fields named
+//! `inbound` and `outbound` as independent rings. Bookkeeping fields are prefixed
+//! with the ring name (`inbound_head`, ...) and every method except `new()` is
+//! suffixed with it (`enqueue_inbound()`, `len_outbound()`, ...).
+//!
+//! ## Options
+//!
+//! Extra flags may follow the capacity, separated by commas:
+//!
+//! - `error_enum` - `enqueue` returns `Result<(), <Name>EnqueueError<T>>` instead of
+//!   `Result<(), T>`; the rejected item is carried in the `Full` variant
+//! - `overwrite` - `enqueue` on a full buffer evicts the oldest item instead of failing and
+//!   returns `Result<Option<T>, _>` carrying the evicted item;
+//!   `set_on_overflow(Box<dyn FnMut(&T) + Send>)` registers a callback observing each
+//!   eviction
+//! - `hash` - implements `Hash` over the live items in FIFO order, then any other fields
+//!   (requires `T: Hash`)
+//! - `display` - implements `Display` as `[a, b, c]` over the live items in FIFO order
+//!   (requires `T: Display`); multiple rings are printed in declaration order
+//! - `allow_zero` - accepts a capacity of `0`, producing a buffer that is always empty and
+//!   always full: `enqueue` fails and `dequeue` returns `None`
+//! - `eq` - implements `PartialEq` and `Eq` comparing the live items in FIFO order, then any
+//!   other fields, consistent with `hash` (each impl requires the matching bound on `T`)
+//! - `ord` - implements `PartialEq`, `Eq`, `PartialOrd` and `Ord` comparing the live items
+//!   lexicographically in FIFO order, then any other fields (each impl requires the matching
+//!   bound on `T`)
+//! - `sync` - also generates `Sync<Name>`, a `Mutex`-backed wrapper with locking
+//!   `enqueue()`, `dequeue()`, `len()`, `is_empty()` and `lock()` for everything else
+//! - `method_vis = "pub(crate)"` - visibility for generated methods (and the error enum)
+//!   instead of the struct's own
+//! - `view_name = "Name"`, `capacity_error_name = "Name"` - rename the generated `<Name>View`
+//!   and `<Name>CapacityError` types, which every buffer emits next to the struct, e.g. when
+//!   those names are already taken
+//! - `enqueue_error_name = "Name"`, `sync_name = "Name"` - rename the `<Name>EnqueueError`
+//!   and `Sync<Name>` types emitted by `error_enum` and `sync`
+//! - `numeric` - adds `sum()` for element types implementing `Sum + Copy`
+//! - `index = "u16"` - stores `capacity`, `head`, `tail` and `size` as `u8`, `u16`, `u32`,
+//!   `u64` or `usize` (the default); the capacity must fit the type
+//! - `spare_slots` - adds `spare_slots_mut()`, borrowing the free slots as
+//!   `&mut [MaybeUninit<T>]`, and `unsafe fn commit(n)` to make the first `n` written slots live
+//! - `unchecked` - adds `unsafe fn enqueue_unchecked(item)`, which skips the full check; the
+//!   caller guarantees space, and debug builds assert it
+//! - `vecdeque_api` - adds `push_back(item)` and `pop_front()` as aliases for `enqueue` and
+//!   `dequeue`, to ease migrating from `VecDeque`
+//! - `any_vec` - accepts any single-argument generic path as the storage type, such as a
+//!   `type Buf<T> = Vec<T>` alias; the type must behave like `Vec<T>`
+//! - `test` - also emits a `#[cfg(test)]` module of empty, full and wraparound smoke tests
+//!   for the buffer, using `T::default()` as sample items (requires `T: Default` and a struct
+//!   without type parameters)
+//! - `max_elem_size = 8` - fails compilation if `size_of::<T>()` exceeds the given number of
+//!   bytes; for a generic struct the check runs when `new()` is instantiated
+//! - `on_full = "grow"` - `enqueue` on a full buffer doubles the capacity instead of failing;
+//!   `max = 64` caps the growth, and `enqueue` fails once full at the cap (`"reject"` and
+//!   `"overwrite"` select the default and `overwrite` behaviors)
+//! - `const_new` - makes `new()` a `const fn` usable in `const` and `static` items; storage
+//!   is allocated by the first `enqueue` instead of up front
+//!
+//! ## Derive Form
+//!
+//! `#[derive(RingBuffer)]` with a `#[ring_capacity(...)]` helper attribute generates the
+//! same methods but leaves the struct untouched, so it must declare `capacity`, `head`,
+//! `tail` and `size` (and `on_overflow` with `overwrite`) itself.
 //!
 //! ## Requirements
 //!
-//! - Struct must have a field named `data` of type `Vec<T>`
+//! - Struct must have a field named `data` (or each named ring) of type `Vec<T>`, or `[T; N]`
+//!   with the attribute form
 //! - Element type `T` must implement `Clone`
+//! - Any other fields must implement `Default`; `new()` initialises them with `Default::default()`,
+//!   so `const_new` does not allow other fields
 
 mod error;
 mod generator;
@@ -37,8 +177,8 @@ use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
 use error::Result;
-use generator::{add_fields, generate_impl};
-use parser::{find_data_field, RingBufferArgs};
+use generator::{add_fields, check_fields, generate_impl, generate_smoke_tests};
+use parser::{find_ring_capacity, find_rings, RingBufferArgs};
 
 /// Transforms a struct with a `Vec<T>` field into a fixed-size FIFO ring buffer.
 ///
@@ -53,8 +193,19 @@ use parser::{find_data_field, RingBufferArgs};
 ///
 /// Adds fields: `capacity`, `head`, `tail`, `size`
 ///
-/// Generates methods: `new()`, `enqueue()`, `dequeue()`, `is_full()`, `is_empty()`,
-/// `len()`, `capacity()`, `clear()`
+/// Generates methods: `new()`, `from_slice()`, `from_iter_truncating()`, `with_contents()`,
+/// `try_with_capacity()`, `try_from_iter()`, `enqueue()`, `enqueue_ref()`,
+/// `saturating_enqueue()`, `enqueue_overwrite_oldest()`, `try_extend()`, `insert()`,
+/// `dequeue()`, `dequeue_into()`, `try_dequeue()`, `remove()`, `take_where()`, `dequeue_if()`,
+/// `append()`, `merge()`, `peek_n()`, `get()`, `peek_mut()`, `peek_pair()`, `position_of()`,
+/// `count_where()`, `count_matching()`, `fold()`, `replace_front()`, `to_vec()`,
+/// `clone_contents_into()`, `into_vec()`, `most_recent()`, `swap()`, `windows()`, `chunks()`,
+/// `is_full()`, `is_empty()`, `len()`, `capacity()`, `occupancy()`, `is_at_least_half_full()`,
+/// `reversed_iter()`, `iter_mut()`, `drain_rev()`, `as_slices()`, `snapshot()`,
+/// `split_index()`, `is_contiguous()`, `physical_head()`, `physical_tail()`,
+/// `logical_to_physical()`, `make_contiguous()`, `truncate()`, `resize()`,
+/// `try_set_capacity()`, `extract_if()`, `drain_filter()`, `clear()`, `clear_with()`,
+/// `clear_and_shrink()`, `reset_with()`, `clone_into()`
 #[proc_macro_attribute]
 pub fn ring_buffer(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as RingBufferArgs);
@@ -67,22 +218,72 @@ pub fn ring_buffer(args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 fn expand_ring_buffer(args: RingBufferArgs, input: &mut DeriveInput) -> Result<TokenStream> {
-    let capacity = args.capacity;
-
-    // Find and validate the data field
-    let element_type = find_data_field(input)?;
+    // Find and validate the data field of every ring
+    let rings = find_rings(input, &args)?;
 
     // Add the additional fields
-    add_fields(input)?;
+    add_fields(input, &rings, &args)?;
 
     // Generate the implementation
-    let implementation = generate_impl(input, &element_type, capacity);
+    let implementation = generate_impl(input, &rings, &args)?;
+    let smoke_tests = generate_smoke_tests(input, &rings, &args)?;
 
     let expanded = quote! {
         #input
 
         #implementation
+
+        #smoke_tests
     };
 
     Ok(expanded.into())
 }
+
+/// Derive form of [`macro@ring_buffer`] for structs that declare every field themselves.
+///
+/// The `#[ring_capacity(...)]` helper attribute takes the same arguments as
+/// `#[ring_buffer(...)]`. Nothing is added to the struct, so it must already declare the
+/// bookkeeping fields: `capacity`, `head`, `tail` and `size` (all `usize`), plus
+/// `on_overflow: Option<Box<dyn FnMut(&T) + Send>>` with `overwrite`. Named rings prefix
+/// each with the ring name.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(RingBuffer)]
+/// #[ring_capacity(10)]
+/// struct MyBuffer {
+///     data: Vec<String>,
+///     capacity: usize,
+///     head: usize,
+///     tail: usize,
+///     size: usize,
+/// }
+/// ```
+#[proc_macro_derive(RingBuffer, attributes(ring_capacity))]
+pub fn derive_ring_buffer(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_derive_ring_buffer(&input) {
+        Ok(tokens) => tokens,
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_derive_ring_buffer(input: &DeriveInput) -> Result<TokenStream> {
+    let args = find_ring_capacity(input)?;
+    let rings = find_rings(input, &args)?;
+
+    // The struct is left untouched, so the bookkeeping fields must already exist
+    check_fields(input, &rings, &args)?;
+
+    let implementation = generate_impl(input, &rings, &args)?;
+    let smoke_tests = generate_smoke_tests(input, &rings, &args)?;
+
+    Ok(quote! {
+        #implementation
+
+        #smoke_tests
+    }
+    .into())
+}
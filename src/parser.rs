@@ -1,37 +1,495 @@
 use crate::error::{Error, Result};
+use proc_macro2::Span;
+use quote::format_ident;
 use syn::{
-    parse::Parse, parse::ParseStream, spanned::Spanned, Data, DeriveInput, Fields, LitInt, Type,
-    TypePath,
+    parse::Parse, parse::ParseStream, spanned::Spanned, Data, DeriveInput, Expr, ExprLit, Fields,
+    FieldsNamed, Ident, Lit, LitInt, LitStr, Token, Type, TypeArray, TypePath, Visibility,
 };
 
 /// Arguments for the ring_buffer attribute macro
 pub struct RingBufferArgs {
+    pub rings: Vec<RingSpec>,
+    pub error_enum: bool,
+    pub numeric: bool,
+    pub overwrite: bool,
+    pub hash: bool,
+    pub display: bool,
+    pub sync: bool,
+    pub eq: bool,
+    pub ord: bool,
+    pub const_new: bool,
+    pub spare_slots: bool,
+    pub unchecked: bool,
+    pub any_vec: bool,
+    pub test: bool,
+    pub vecdeque_api: bool,
+    pub grow: bool,
+    pub max_capacity: Option<usize>,
+    pub max_elem_size: Option<usize>,
+    pub allow_zero: bool,
+    pub method_vis: Option<Visibility>,
+    pub index_type: Option<Ident>,
+    pub view_name: Option<Ident>,
+    pub capacity_error_name: Option<Ident>,
+    pub enqueue_error_name: Option<Ident>,
+    pub sync_name: Option<Ident>,
+}
+
+/// One ring requested in the attribute: `5` for the `data` field, or `name = 5`
+pub struct RingSpec {
+    pub name: Option<Ident>,
     pub capacity: usize,
+    pub capacity_span: Span,
+    /// No capacity was given, so it is read from an array-typed `data` field
+    pub inferred: bool,
 }
 
 impl Parse for RingBufferArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let capacity_lit: LitInt = input.parse()?;
-        let capacity = capacity_lit
-            .base10_parse::<usize>()
-            .map_err(|_| syn::Error::new(capacity_lit.span(), "capacity must be a valid usize"))?;
+        let mut rings: Vec<RingSpec> = Vec::new();
+        let mut error_enum = false;
+        let mut numeric = false;
+        let mut overwrite = false;
+        let mut hash = false;
+        let mut display = false;
+        let mut sync = false;
+        let mut eq = false;
+        let mut ord = false;
+        let mut const_new = false;
+        let mut spare_slots = false;
+        let mut unchecked = false;
+        let mut any_vec = false;
+        let mut test = false;
+        let mut vecdeque_api = false;
+        let mut allow_zero = false;
+        let mut grow = false;
+        let mut max_capacity: Option<(usize, Span)> = None;
+        let mut max_elem_size = None;
+        let mut method_vis = None;
+        let mut index_type: Option<Ident> = None;
+        let mut view_name = None;
+        let mut capacity_error_name = None;
+        let mut enqueue_error_name = None;
+        let mut sync_name = None;
+        let mut seen_options: Vec<Ident> = Vec::new();
+
+        if input.peek(LitInt) {
+            let (capacity, capacity_span) = parse_capacity(input)?;
+            rings.push(RingSpec {
+                name: None,
+                capacity,
+                capacity_span,
+                inferred: false,
+            });
+        }
+
+        let mut needs_comma = !rings.is_empty();
+        while !input.is_empty() {
+            if needs_comma {
+                if !input.peek(Token![,]) {
+                    return Err(input.error("expected `,` between ring_buffer arguments"));
+                }
+                input.parse::<Token![,]>()?;
+
+                // A trailing comma ends the arguments
+                if input.is_empty() {
+                    break;
+                }
+            }
+            needs_comma = true;
+
+            let ident: Ident = input.parse()?;
+
+            if OPTIONS.iter().any(|option| ident == option) {
+                if seen_options.contains(&ident) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("duplicate ring_buffer option `{}`", ident),
+                    ));
+                }
+                seen_options.push(ident.clone());
+            }
+
+            if ident == "method_vis" {
+                input.parse::<Token![=]>()?;
+                let vis_lit: LitStr = input.parse()?;
+                let vis: Visibility = vis_lit.parse().map_err(|_| {
+                    syn::Error::new(
+                        vis_lit.span(),
+                        "method_vis must be a visibility such as \"pub(crate)\"",
+                    )
+                })?;
+                method_vis = Some(vis);
+            } else if ident == "index" {
+                input.parse::<Token![=]>()?;
+                let index_lit: LitStr = input.parse()?;
+                let index = index_lit.value();
+                if !INDEX_TYPES.iter().any(|(name, _)| *name == index) {
+                    return Err(syn::Error::new(
+                        index_lit.span(),
+                        "index must be one of \"u8\", \"u16\", \"u32\", \"u64\" or \"usize\"",
+                    ));
+                }
+                index_type = Some(Ident::new(&index, index_lit.span()));
+            } else if ident == "view_name"
+                || ident == "capacity_error_name"
+                || ident == "enqueue_error_name"
+                || ident == "sync_name"
+            {
+                input.parse::<Token![=]>()?;
+                let name_lit: LitStr = input.parse()?;
+                let name: Ident = name_lit.parse().map_err(|_| {
+                    syn::Error::new(
+                        name_lit.span(),
+                        format!("{} must be a valid Rust identifier", ident),
+                    )
+                })?;
+                if ident == "view_name" {
+                    view_name = Some(name);
+                } else if ident == "capacity_error_name" {
+                    capacity_error_name = Some(name);
+                } else if ident == "enqueue_error_name" {
+                    enqueue_error_name = Some(name);
+                } else {
+                    sync_name = Some(name);
+                }
+            } else if ident == "on_full" {
+                input.parse::<Token![=]>()?;
+                let policy_lit: LitStr = input.parse()?;
+                match policy_lit.value().as_str() {
+                    "reject" => {}
+                    "overwrite" => overwrite = true,
+                    "grow" => grow = true,
+                    _ => {
+                        return Err(syn::Error::new(
+                            policy_lit.span(),
+                            "on_full must be one of \"reject\", \"overwrite\" or \"grow\"",
+                        ))
+                    }
+                }
+            } else if ident == "max_elem_size" {
+                input.parse::<Token![=]>()?;
+                let size_lit: LitInt = input.parse()?;
+                match size_lit.base10_parse::<usize>() {
+                    Ok(size) if size > 0 => max_elem_size = Some(size),
+                    _ => {
+                        return Err(syn::Error::new(
+                            size_lit.span(),
+                            "max_elem_size must be a positive integer number of bytes",
+                        ))
+                    }
+                }
+            } else if ident == "max" {
+                input.parse::<Token![=]>()?;
+                max_capacity = Some(parse_capacity(input)?);
+            } else if input.peek(Token![=]) {
+                input.parse::<Token![=]>()?;
+                let (capacity, capacity_span) = parse_capacity(input)?;
+
+                if rings.iter().any(|r| r.name.is_none()) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "named rings cannot be combined with an unnamed capacity",
+                    ));
+                }
+                if rings.iter().any(|r| r.name.as_ref() == Some(&ident)) {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("duplicate ring `{}`", ident),
+                    ));
+                }
+
+                rings.push(RingSpec {
+                    name: Some(ident),
+                    capacity,
+                    capacity_span,
+                    inferred: false,
+                });
+            } else if ident == "error_enum" {
+                error_enum = true;
+            } else if ident == "numeric" {
+                numeric = true;
+            } else if ident == "overwrite" {
+                overwrite = true;
+            } else if ident == "hash" {
+                hash = true;
+            } else if ident == "display" {
+                display = true;
+            } else if ident == "sync" {
+                sync = true;
+            } else if ident == "eq" {
+                eq = true;
+            } else if ident == "ord" {
+                ord = true;
+            } else if ident == "const_new" {
+                const_new = true;
+            } else if ident == "spare_slots" {
+                spare_slots = true;
+            } else if ident == "unchecked" {
+                unchecked = true;
+            } else if ident == "any_vec" {
+                any_vec = true;
+            } else if ident == "test" {
+                test = true;
+            } else if ident == "vecdeque_api" {
+                vecdeque_api = true;
+            } else if ident == "allow_zero" {
+                allow_zero = true;
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("unknown ring_buffer option `{}`", ident),
+                ));
+            }
+        }
+
+        // Without a capacity, the `data` field must be an array whose length supplies it
+        if rings.is_empty() {
+            rings.push(RingSpec {
+                name: None,
+                capacity: 0,
+                capacity_span: Span::call_site(),
+                inferred: true,
+            });
+        }
 
-        if capacity == 0 {
+        // Growing into the oldest item's slot would make it an overwrite
+        if grow && overwrite {
             return Err(syn::Error::new(
-                capacity_lit.span(),
-                "capacity must be greater than 0",
+                Span::call_site(),
+                "on_full = \"grow\" cannot be combined with `overwrite`",
             ));
         }
 
-        Ok(RingBufferArgs { capacity })
+        if let Some((max, max_span)) = max_capacity {
+            if !grow {
+                return Err(syn::Error::new(
+                    max_span,
+                    "max only applies with on_full = \"grow\"",
+                ));
+            }
+            if let Some(index_type) = &index_type {
+                if max as u128 > index_type_max(index_type) {
+                    return Err(syn::Error::new(
+                        max_span,
+                        format!("max `{}` does not fit in index type `{}`", max, index_type),
+                    ));
+                }
+            }
+        }
+
+        let args = RingBufferArgs {
+            rings,
+            error_enum,
+            numeric,
+            overwrite,
+            hash,
+            display,
+            sync,
+            eq,
+            ord,
+            const_new,
+            spare_slots,
+            unchecked,
+            any_vec,
+            test,
+            vecdeque_api,
+            grow,
+            max_capacity: max_capacity.map(|(max, _)| max),
+            max_elem_size,
+            allow_zero,
+            method_vis,
+            index_type,
+            view_name,
+            capacity_error_name,
+            enqueue_error_name,
+            sync_name,
+        };
+
+        for spec in args.rings.iter().filter(|spec| !spec.inferred) {
+            args.check_capacity(spec.capacity, spec.capacity_span)?;
+        }
+
+        Ok(args)
+    }
+}
+
+impl RingBufferArgs {
+    /// Check a ring's capacity, given or inferred, against `allow_zero`, `max` and `index`
+    pub fn check_capacity(&self, capacity: usize, span: Span) -> syn::Result<()> {
+        if capacity == 0 && !self.allow_zero {
+            return Err(syn::Error::new(
+                span,
+                "capacity must be greater than 0 (use `allow_zero` for an always-empty buffer)",
+            ));
+        }
+
+        if let Some(max) = self.max_capacity {
+            if capacity > max {
+                return Err(syn::Error::new(
+                    span,
+                    format!("capacity `{}` exceeds max `{}`", capacity, max),
+                ));
+            }
+        }
+
+        // Every index and count is at most the capacity, so the capacity must fit the type
+        if let Some(index_type) = &self.index_type {
+            if capacity as u128 > index_type_max(index_type) {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "capacity `{}` does not fit in index type `{}`",
+                        capacity, index_type
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Names of the `ring_buffer` options, each of which may appear at most once
+const OPTIONS: &[&str] = &[
+    "method_vis",
+    "index",
+    "view_name",
+    "capacity_error_name",
+    "enqueue_error_name",
+    "sync_name",
+    "on_full",
+    "max_elem_size",
+    "max",
+    "error_enum",
+    "numeric",
+    "overwrite",
+    "hash",
+    "display",
+    "sync",
+    "eq",
+    "ord",
+    "const_new",
+    "spare_slots",
+    "unchecked",
+    "any_vec",
+    "test",
+    "vecdeque_api",
+    "allow_zero",
+];
+
+/// Unsigned types accepted by the `index` option, with their maximum values
+const INDEX_TYPES: &[(&str, u128)] = &[
+    ("u8", u8::MAX as u128),
+    ("u16", u16::MAX as u128),
+    ("u32", u32::MAX as u128),
+    ("u64", u64::MAX as u128),
+    ("usize", usize::MAX as u128),
+];
+
+fn index_type_max(index_type: &Ident) -> u128 {
+    INDEX_TYPES
+        .iter()
+        .find(|(name, _)| index_type == name)
+        .map(|(_, max)| *max)
+        .unwrap_or(usize::MAX as u128)
+}
+
+/// Read the derive form's `#[ring_capacity(...)]` attribute, which takes the same arguments
+/// as `#[ring_buffer(...)]`
+pub fn find_ring_capacity(input: &DeriveInput) -> Result<RingBufferArgs> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("ring_capacity"))
+        .ok_or_else(|| Error::missing_ring_capacity(input.ident.span()))?;
+
+    Ok(attr.parse_args::<RingBufferArgs>()?)
+}
+
+/// Parse a capacity literal, rejecting values no `Vec` could ever allocate
+fn parse_capacity(input: ParseStream) -> syn::Result<(usize, Span)> {
+    let capacity_lit: LitInt = input.parse()?;
+    let capacity = capacity_lit.base10_parse::<usize>().map_err(|_| {
+        syn::Error::new(
+            capacity_lit.span(),
+            format!("capacity `{}` does not fit in a usize", capacity_lit),
+        )
+    })?;
+
+    // `Vec::with_capacity` panics beyond `isize::MAX` bytes, so this is a hard upper bound;
+    // the element size is unknown here, so the full size is checked by the generated code
+    if capacity > isize::MAX as usize {
+        return Err(syn::Error::new(
+            capacity_lit.span(),
+            format!(
+                "capacity `{}` exceeds isize::MAX and can never be allocated; use a smaller capacity",
+                capacity_lit
+            ),
+        ));
+    }
+
+    Ok((capacity, capacity_lit.span()))
+}
+
+/// A validated ring: its storage field and the bookkeeping fields generated for it
+pub struct Ring {
+    pub field: Ident,
+    pub element_type: Type,
+    pub capacity: usize,
+    pub capacity_field: Ident,
+    pub head_field: Ident,
+    pub tail_field: Ident,
+    pub size_field: Ident,
+    pub on_overflow_field: Ident,
+    suffix: Option<Ident>,
+}
+
+impl Ring {
+    fn new(spec: &RingSpec, element_type: Type, capacity: usize) -> Self {
+        match &spec.name {
+            None => Ring {
+                field: Ident::new("data", Span::call_site()),
+                element_type,
+                capacity,
+                capacity_field: Ident::new("capacity", Span::call_site()),
+                head_field: Ident::new("head", Span::call_site()),
+                tail_field: Ident::new("tail", Span::call_site()),
+                size_field: Ident::new("size", Span::call_site()),
+                on_overflow_field: Ident::new("on_overflow", Span::call_site()),
+                suffix: None,
+            },
+            Some(name) => Ring {
+                field: name.clone(),
+                element_type,
+                capacity,
+                capacity_field: format_ident!("{}_capacity", name),
+                head_field: format_ident!("{}_head", name),
+                tail_field: format_ident!("{}_tail", name),
+                size_field: format_ident!("{}_size", name),
+                on_overflow_field: format_ident!("{}_on_overflow", name),
+                suffix: Some(name.clone()),
+            },
+        }
+    }
+
+    /// Name of a generated method, suffixed with the ring name for named rings
+    pub fn method(&self, name: &str) -> Ident {
+        match &self.suffix {
+            None => format_ident!("{}", name),
+            Some(suffix) => format_ident!("{}_{}", name, suffix),
+        }
     }
 }
 
 /// Extract the element type T from Vec<T>
-pub fn extract_vec_element_type(ty: &Type) -> Result<Type> {
+///
+/// With `any_vec`, any path with a generic argument is accepted as the container, so a
+/// `Vec` alias such as `Buf<T>` works; the type must still behave like `Vec<T>`.
+pub fn extract_vec_element_type(ty: &Type, any_vec: bool) -> Result<Type> {
     if let Type::Path(TypePath { path, .. }) = ty {
         if let Some(segment) = path.segments.last() {
-            if segment.ident != "Vec" {
+            if segment.ident != "Vec" && !any_vec {
                 return Err(Error::invalid_data_field_type(segment.ident.span()));
             }
 
@@ -46,15 +504,45 @@ pub fn extract_vec_element_type(ty: &Type) -> Result<Type> {
     Err(Error::invalid_data_field_type(ty.span()))
 }
 
-/// Find and validate the 'data' field in the struct
-pub fn find_data_field(input: &DeriveInput) -> Result<Type> {
-    let fields = match &input.data {
+/// Extract the element type of a ring's storage field, and the length `N` when it is `[T; N]`
+///
+/// An array field only declares the element type and capacity; the attribute replaces it
+/// with `Vec<T>` storage.
+pub fn extract_storage_type(ty: &Type, any_vec: bool) -> Result<(Type, Option<(usize, Span)>)> {
+    if let Type::Array(TypeArray { elem, len, .. }) = ty {
+        let len_lit = match len {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(len_lit),
+                ..
+            }) => len_lit,
+            _ => return Err(Error::array_length_not_literal(len.span())),
+        };
+        let len = len_lit.base10_parse::<usize>()?;
+        return Ok(((**elem).clone(), Some((len, len_lit.span()))));
+    }
+
+    Ok((extract_vec_element_type(ty, any_vec)?, None))
+}
+
+/// Get the named fields of the struct, rejecting enums, unions, tuple and unit structs
+fn named_fields(input: &DeriveInput) -> Result<&FieldsNamed> {
+    match &input.data {
         Data::Struct(data_struct) => match &data_struct.fields {
-            Fields::Named(fields) => fields,
-            _ => return Err(Error::not_named_fields(input.ident.span())),
+            Fields::Named(fields) => Ok(fields),
+            _ => Err(Error::not_named_fields(input.ident.span())),
         },
-        _ => return Err(Error::not_a_struct(input.ident.span())),
-    };
+        Data::Enum(_) => Err(Error::enum_not_supported(input.ident.span())),
+        Data::Union(_) => Err(Error::union_not_supported(input.ident.span())),
+    }
+}
+
+/// Find and validate the 'data' field in the struct, returning its element type and array
+/// length if it is `[T; N]`
+pub fn find_data_field(
+    input: &DeriveInput,
+    any_vec: bool,
+) -> Result<(Type, Option<(usize, Span)>)> {
+    let fields = named_fields(input)?;
 
     let data_field = fields
         .named
@@ -62,8 +550,45 @@ pub fn find_data_field(input: &DeriveInput) -> Result<Type> {
         .find(|f| f.ident.as_ref().map(|i| i == "data").unwrap_or(false));
 
     if let Some(field) = data_field {
-        extract_vec_element_type(&field.ty)
+        extract_storage_type(&field.ty, any_vec)
     } else {
         Err(Error::missing_data_field(input.ident.span()))
     }
 }
+
+/// Find and validate the storage field for every ring requested in the attribute
+pub fn find_rings(input: &DeriveInput, args: &RingBufferArgs) -> Result<Vec<Ring>> {
+    let fields = named_fields(input)?;
+
+    args.rings
+        .iter()
+        .map(|spec| {
+            let (element_type, array_len) = match &spec.name {
+                None => find_data_field(input, args.any_vec)?,
+                Some(name) => {
+                    let field = fields
+                        .named
+                        .iter()
+                        .find(|f| f.ident.as_ref() == Some(name))
+                        .ok_or_else(|| Error::missing_ring_field(name.span(), name))?;
+                    extract_storage_type(&field.ty, args.any_vec)?
+                }
+            };
+
+            // An array length supplies a missing capacity, and must agree with a given one
+            let capacity = match (array_len, spec.inferred) {
+                (Some((len, len_span)), true) => {
+                    args.check_capacity(len, len_span)?;
+                    len
+                }
+                (Some((len, len_span)), false) if len != spec.capacity => {
+                    return Err(Error::conflicting_capacity(len_span, spec.capacity, len));
+                }
+                (None, true) => return Err(Error::missing_capacity(spec.capacity_span)),
+                _ => spec.capacity,
+            };
+
+            Ok(Ring::new(spec, element_type, capacity))
+        })
+        .collect()
+}
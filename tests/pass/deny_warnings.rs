@@ -0,0 +1,23 @@
+//! Only `new()` and `len()` are used, so every other generated item would be dead code.
+#![deny(warnings)]
+
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(4)]
+struct Buffer {
+    data: Vec<u8>,
+}
+
+#[ring_buffer(inbound = 2, outbound = 3)]
+struct Pair<T: Clone> {
+    inbound: Vec<T>,
+    outbound: Vec<String>,
+}
+
+fn main() {
+    let buf = Buffer::new();
+    assert_eq!(buf.len(), 0);
+
+    let pair = Pair::<u32>::new();
+    assert_eq!(pair.len_inbound(), 0);
+}
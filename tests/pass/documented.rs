@@ -0,0 +1,19 @@
+//! Every generated public item must carry documentation.
+#![deny(missing_docs)]
+
+use ring_buffer_macro::ring_buffer;
+
+/// Exercises the methods added by `error_enum`, `numeric` and `sync`.
+#[ring_buffer(4, error_enum, numeric, sync)]
+pub struct Samples {
+    data: Vec<i32>,
+}
+
+/// Exercises per-ring methods and the overwrite-only methods.
+#[ring_buffer(inbound = 3, outbound = 2, overwrite)]
+pub struct Connection {
+    inbound: Vec<String>,
+    outbound: Vec<String>,
+}
+
+fn main() {}
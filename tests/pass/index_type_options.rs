@@ -0,0 +1,30 @@
+//! A narrow `index` type must compile alongside every other option.
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(8, index = "u8", overwrite, error_enum, numeric, sync, eq, hash, display, spare_slots, const_new)]
+struct Everything {
+    data: Vec<i64>,
+}
+
+#[ring_buffer(a = 2, b = 3, index = "u32", ord)]
+struct Named {
+    a: Vec<u8>,
+    b: Vec<String>,
+}
+
+fn main() {
+    let mut buf = Everything::new();
+    for i in 0..20 {
+        let _ = buf.enqueue(i);
+    }
+    assert_eq!(buf.sum(), (12..20).sum::<i64>());
+    buf.truncate(2);
+    let spare = buf.spare_slots_mut().len();
+    assert_eq!(spare, 6);
+
+    let mut named = Named::new();
+    named.enqueue_a(1).unwrap();
+    named.enqueue_b("x".to_string()).unwrap();
+    assert_eq!(named.extract_if_b(|_| true).len(), 1);
+    assert!(named.is_empty_b() && named.len_a() == 1);
+}
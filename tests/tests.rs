@@ -1,6 +1,7 @@
 #![allow(clippy::vec_box, clippy::box_collection)]
 
-use ring_buffer_macro::ring_buffer;
+use ring_buffer_macro::{ring_buffer, RingBuffer};
+use std::borrow::Cow;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -847,3 +848,2947 @@ fn test_large_capacity() {
     }
     assert_eq!(buf.len(), 500);
 }
+
+#[ring_buffer(2, error_enum)]
+struct ErrorEnumBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(1, error_enum)]
+struct GenericErrorEnumBuffer<T: Clone> {
+    data: Vec<T>,
+}
+
+// Test error_enum flag reports a full buffer through the Full variant
+#[test]
+fn test_error_enum_full_variant() {
+    let mut buf = ErrorEnumBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    match buf.enqueue(3) {
+        Err(ErrorEnumBufferEnqueueError::Full(item)) => assert_eq!(item, 3),
+        Ok(()) => panic!("expected enqueue to fail on a full buffer"),
+    }
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.dequeue(), Some(1));
+    assert_eq!(buf.dequeue(), Some(2));
+}
+
+// Test error_enum recovers the item via into_inner and formats sensibly
+#[test]
+fn test_error_enum_into_inner_and_display() {
+    let mut buf: GenericErrorEnumBuffer<String> = GenericErrorEnumBuffer::new();
+    buf.enqueue("kept".to_string()).unwrap();
+    let err = buf.enqueue("rejected".to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "ring buffer is full");
+    assert_eq!(
+        err,
+        GenericErrorEnumBufferEnqueueError::Full("rejected".to_string())
+    );
+    assert_eq!(err.into_inner(), "rejected".to_string());
+    assert_eq!(buf.dequeue(), Some("kept".to_string()));
+}
+
+// Test dequeue_if removes the front element when the predicate accepts it
+#[test]
+fn test_dequeue_if_predicate_passes() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.dequeue_if(|&x| x == 1), Some(1));
+    assert_eq!(buf.len(), 1);
+    assert_eq!(buf.dequeue(), Some(2));
+}
+
+// Test dequeue_if leaves the buffer untouched when the predicate rejects
+#[test]
+fn test_dequeue_if_predicate_fails() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.dequeue_if(|&x| x > 1), None);
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.dequeue(), Some(1));
+    assert_eq!(buf.dequeue(), Some(2));
+}
+
+// Test dequeue_if on an empty buffer never calls the predicate
+#[test]
+fn test_dequeue_if_empty() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.dequeue_if(|_| panic!("predicate called")), None);
+    assert!(buf.is_empty());
+}
+
+// Test saturating_enqueue reports success while there is room
+#[test]
+fn test_saturating_enqueue_returns_true() {
+    let mut buf = TestBuffer::new();
+    assert!(buf.saturating_enqueue(1));
+    assert!(buf.saturating_enqueue(2));
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.dequeue(), Some(1));
+}
+
+// Test saturating_enqueue drops the item and preserves state when full
+#[test]
+fn test_saturating_enqueue_full_preserves_state() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        assert!(buf.saturating_enqueue(i));
+    }
+    assert!(!buf.saturating_enqueue(6));
+    assert!(buf.is_full());
+    assert_eq!(buf.len(), 5);
+    for i in 1..=5 {
+        assert_eq!(buf.dequeue(), Some(i));
+    }
+    assert!(buf.is_empty());
+}
+
+// Test from_slice with fewer items than capacity
+#[test]
+fn test_from_slice_shorter_than_capacity() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3]);
+    assert_eq!(buf.len(), 3);
+    assert!(!buf.is_full());
+    assert_eq!(buf.dequeue(), Some(1));
+    assert_eq!(buf.dequeue(), Some(2));
+    assert_eq!(buf.dequeue(), Some(3));
+}
+
+// Test from_slice with exactly capacity items fills the buffer
+#[test]
+fn test_from_slice_equal_to_capacity() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3, 4, 5]);
+    assert!(buf.is_full());
+    for i in 1..=5 {
+        assert_eq!(buf.dequeue(), Some(i));
+    }
+}
+
+// Test from_slice keeps only the first capacity items when given too many
+#[test]
+fn test_from_slice_longer_than_capacity() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+    assert!(buf.is_full());
+    assert_eq!(buf.len(), 5);
+    for i in 1..=5 {
+        assert_eq!(buf.dequeue(), Some(i));
+    }
+    assert!(buf.is_empty());
+}
+
+// Test mixed operations never trip the generated debug assertions
+#[test]
+#[cfg(debug_assertions)]
+fn test_debug_assertions_hold_during_operations() {
+    let mut buf = TestBuffer::new();
+    for round in 0..20 {
+        for i in 0..(round % 7) {
+            let _ = buf.enqueue(i);
+        }
+        for _ in 0..(round % 4) {
+            buf.dequeue();
+        }
+        if round % 5 == 0 {
+            buf.clear();
+        }
+        assert!(buf.len() <= buf.capacity());
+    }
+}
+
+// Test clear_and_shrink empties the buffer and it keeps working afterwards
+#[test]
+fn test_clear_and_shrink_then_reuse() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.clear_and_shrink();
+    assert!(buf.is_empty());
+    assert_eq!(buf.capacity(), 5);
+    assert_eq!(buf.data.capacity(), 0);
+    for i in 10..15 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(buf.is_full());
+    // Storage is reserved for exactly the capacity again, not grown by doubling
+    assert!(buf.data.capacity() <= buf.capacity());
+    assert!(buf.enqueue(15).is_err());
+    for i in 10..15 {
+        assert_eq!(buf.dequeue(), Some(i));
+    }
+    assert!(buf.is_empty());
+}
+
+// Test swap reorders elements by logical index across the wraparound seam
+#[test]
+fn test_swap_reorders_drain_output() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    buf.swap(0, 4);
+    buf.swap(1, 2);
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![7, 5, 4, 6, 3]);
+}
+
+// Test swap panics when an index is beyond the current length
+#[test]
+#[should_panic]
+fn test_swap_out_of_range_panics() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    buf.swap(0, 2);
+}
+
+#[ring_buffer(inbound = 3, outbound = 2)]
+struct Connection {
+    inbound: Vec<u8>,
+    outbound: Vec<String>,
+}
+
+// Test a struct with two named rings keeps independent bookkeeping
+#[test]
+fn test_multiple_rings_are_independent() {
+    let mut conn = Connection::new();
+    assert_eq!(conn.capacity_inbound(), 3);
+    assert_eq!(conn.capacity_outbound(), 2);
+
+    conn.enqueue_inbound(1).unwrap();
+    conn.enqueue_inbound(2).unwrap();
+    conn.enqueue_outbound("a".to_string()).unwrap();
+    conn.enqueue_outbound("b".to_string()).unwrap();
+
+    assert_eq!(conn.len_inbound(), 2);
+    assert!(conn.is_full_outbound());
+    assert!(!conn.is_full_inbound());
+    assert!(conn.enqueue_outbound("c".to_string()).is_err());
+
+    assert_eq!(conn.dequeue_outbound(), Some("a".to_string()));
+    assert_eq!(conn.len_inbound(), 2);
+    assert_eq!(conn.dequeue_inbound(), Some(1));
+
+    conn.clear_inbound();
+    assert!(conn.is_empty_inbound());
+    assert_eq!(conn.dequeue_outbound(), Some("b".to_string()));
+    assert!(conn.is_empty_outbound());
+}
+
+// Test named rings wrap around independently of each other
+#[test]
+fn test_multiple_rings_wraparound() {
+    let mut conn = Connection::new();
+    for round in 0..10u8 {
+        conn.enqueue_inbound(round).unwrap();
+        conn.enqueue_outbound(round.to_string()).unwrap();
+        assert_eq!(conn.dequeue_inbound(), Some(round));
+        assert_eq!(conn.dequeue_outbound(), Some(round.to_string()));
+    }
+    assert!(conn.is_empty_inbound());
+    assert!(conn.is_empty_outbound());
+}
+
+#[ring_buffer(4, numeric)]
+struct NumericBuffer {
+    data: Vec<i32>,
+}
+
+// Test sum adds up the live elements of a numeric buffer
+#[test]
+fn test_numeric_sum() {
+    let mut buf = NumericBuffer::new();
+    assert_eq!(buf.sum(), 0);
+    for i in 1..=4 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.sum(), 10);
+    assert_eq!(buf.sum() / buf.len() as i32, 2);
+}
+
+// Test sum ignores stale slots left behind by dequeue and clear
+#[test]
+fn test_numeric_sum_excludes_stale_slots() {
+    let mut buf = NumericBuffer::new();
+    for i in 1..=4 {
+        buf.enqueue(i * 10).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(1).unwrap();
+    assert_eq!(buf.sum(), 30 + 40 + 1);
+    buf.clear();
+    assert_eq!(buf.sum(), 0);
+}
+
+// Test windows yields overlapping windows in logical order across the seam
+#[test]
+fn test_windows_on_wrapped_buffer() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    let windows: Vec<Vec<&i32>> = buf.windows(3).collect();
+    assert_eq!(
+        windows,
+        vec![vec![&3, &4, &5], vec![&4, &5, &6], vec![&5, &6, &7]]
+    );
+    assert_eq!(buf.windows(3).count(), buf.len() - 3 + 1);
+    let whole: Vec<Vec<&i32>> = buf.windows(5).collect();
+    assert_eq!(whole, vec![vec![&3, &4, &5, &6, &7]]);
+}
+
+// Test windows is empty when the window is larger than the contents
+#[test]
+fn test_windows_larger_than_len() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.windows(3).count(), 0);
+    assert_eq!(buf.windows(1).count(), 2);
+}
+
+// Test occupancy reports the fill fraction at empty, half and full
+#[test]
+fn test_occupancy() {
+    let mut buf = NumericBuffer::new();
+    assert_eq!(buf.occupancy(), 0.0);
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.occupancy(), 0.5);
+    buf.enqueue(3).unwrap();
+    buf.enqueue(4).unwrap();
+    assert_eq!(buf.occupancy(), 1.0);
+}
+
+// Test is_at_least_half_full rounds the threshold up for odd capacities
+#[test]
+fn test_is_at_least_half_full() {
+    let mut buf = TestBuffer::new();
+    assert!(!buf.is_at_least_half_full());
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert!(!buf.is_at_least_half_full());
+    buf.enqueue(3).unwrap();
+    assert!(buf.is_at_least_half_full());
+
+    let mut even = NumericBuffer::new();
+    even.enqueue(1).unwrap();
+    assert!(!even.is_at_least_half_full());
+    even.enqueue(2).unwrap();
+    assert!(even.is_at_least_half_full());
+}
+
+// Test the inlined hot-path methods in a tight producer/consumer loop
+#[test]
+fn test_hot_path_loop() {
+    let mut buf = LargeBuffer::new();
+    let mut total = 0i64;
+    for i in 0..10_000 {
+        if buf.is_full() {
+            total += buf.dequeue().unwrap() as i64;
+        }
+        buf.enqueue(i).unwrap();
+    }
+    while !buf.is_empty() {
+        total += buf.dequeue().unwrap() as i64;
+    }
+    assert_eq!(total, (0..10_000i64).sum());
+    assert_eq!(buf.len(), 0);
+    assert_eq!(buf.capacity(), 100);
+}
+
+#[ring_buffer(3, overwrite)]
+struct OverwriteBuffer {
+    data: Vec<i32>,
+}
+
+// Test overwrite mode evicts the oldest element when full
+#[test]
+fn test_overwrite_evicts_oldest() {
+    let mut buf = OverwriteBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(3));
+    assert_eq!(buf.dequeue(), Some(4));
+    assert_eq!(buf.dequeue(), Some(5));
+    assert!(buf.is_empty());
+}
+
+// Test the overflow callback receives exactly the evicted values in order
+#[test]
+fn test_overwrite_overflow_callback() {
+    use std::sync::Mutex;
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let mut buf = OverwriteBuffer::new();
+    let sink = Arc::clone(&evicted);
+    buf.set_on_overflow(Box::new(move |item| sink.lock().unwrap().push(*item)));
+
+    for i in 1..=3 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(evicted.lock().unwrap().is_empty());
+    for i in 4..=7 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(*evicted.lock().unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(buf.len(), 3);
+    assert_eq!(buf.dequeue(), Some(5));
+}
+
+// Test an overwrite buffer stays `Send`, with or without a callback registered
+#[test]
+fn test_overwrite_buffer_is_send() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let mut buf = OverwriteBuffer::new();
+    assert_send(&buf);
+    buf.set_on_overflow(Box::new(|_| {}));
+    buf.enqueue(1).unwrap();
+    let handle = std::thread::spawn(move || buf.dequeue());
+    assert_eq!(handle.join().unwrap(), Some(1));
+}
+
+// Test peek_n borrows the oldest elements across the wraparound seam
+#[test]
+fn test_peek_n_across_seam() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.peek_n(3), vec![&4, &5, &6]);
+    assert_eq!(buf.peek_n(10), vec![&4, &5, &6, &7]);
+    assert!(buf.peek_n(0).is_empty());
+    assert_eq!(buf.len(), 4);
+    assert_eq!(buf.dequeue(), Some(4));
+}
+
+// Test peek_n returns references to the stored elements themselves
+#[test]
+fn test_peek_n_references_stored_elements() {
+    let mut buf = RcBuffer::new();
+    let first = Rc::new("first".to_string());
+    buf.enqueue(first.clone()).unwrap();
+    buf.enqueue(Rc::new("second".to_string())).unwrap();
+    let peeked = buf.peek_n(1);
+    assert!(Rc::ptr_eq(peeked[0], &first));
+    assert_eq!(Rc::strong_count(&first), 2);
+}
+
+// Test resize grows the capacity while preserving FIFO order
+#[test]
+fn test_resize_grow_preserves_order() {
+    let mut buf = GenericBuffer::<i32>::new();
+    for i in 1..=10 {
+        buf.enqueue(i).unwrap();
+    }
+    for _ in 0..7 {
+        buf.dequeue();
+    }
+    buf.enqueue(11).unwrap();
+    buf.resize(3);
+    assert_eq!(buf.capacity(), 3);
+    buf.resize(5);
+    assert_eq!(buf.capacity(), 5);
+    assert_eq!(buf.len(), 3);
+    buf.enqueue(12).unwrap();
+    buf.enqueue(13).unwrap();
+    assert!(buf.is_full());
+    assert!(buf.enqueue(14).is_err());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![9, 10, 11, 12, 13]);
+}
+
+// Test resize below len keeps the newest elements
+#[test]
+fn test_resize_shrink_keeps_newest() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.resize(2);
+    assert_eq!(buf.capacity(), 2);
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(4));
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.dequeue(), Some(5));
+    assert_eq!(buf.dequeue(), Some(6));
+    assert!(buf.is_empty());
+}
+
+// Test resize rejects a zero capacity
+#[test]
+#[should_panic]
+fn test_resize_zero_panics() {
+    let mut buf = TestBuffer::new();
+    buf.resize(0);
+}
+
+// Test try_set_capacity grows a wrapped buffer, keeping its items in order
+#[test]
+fn test_try_set_capacity_grow() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.try_set_capacity(8), Ok(()));
+    assert_eq!(buf.capacity(), 8);
+    for i in 7..=9 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(buf.is_full());
+    assert_eq!(buf.to_vec(), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+}
+
+// Test try_set_capacity rejects 0 and capacities below len without changing the buffer
+#[test]
+fn test_try_set_capacity_errors() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=3 {
+        buf.enqueue(i).unwrap();
+    }
+    let err = buf.try_set_capacity(0).unwrap_err();
+    assert_eq!(err, TestBufferCapacityError::Invalid { capacity: 0 });
+    assert_eq!(err.overflow(), 0);
+    assert_eq!(err.to_string(), "invalid ring buffer capacity 0");
+    let err = buf.try_set_capacity(2).unwrap_err();
+    assert_eq!(err.capacity(), 2);
+    assert_eq!(err.overflow(), 1);
+    assert_eq!(
+        err.to_string(),
+        "3 items do not fit in a ring buffer of capacity 2"
+    );
+    assert_eq!(buf.capacity(), 5);
+    assert_eq!(buf.to_vec(), vec![1, 2, 3]);
+
+    // Shrinking to exactly len is allowed
+    assert_eq!(buf.try_set_capacity(3), Ok(()));
+    assert!(buf.is_full());
+    assert_eq!(buf.to_vec(), vec![1, 2, 3]);
+}
+
+// Test drain_filter removes even values from a wrapped buffer in FIFO order
+#[test]
+fn test_drain_filter_even_values() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    let evens = buf.drain_filter(|x| x % 2 == 0);
+    assert_eq!(evens, vec![4, 6]);
+    assert_eq!(buf.len(), 3);
+    buf.enqueue(8).unwrap();
+    buf.enqueue(9).unwrap();
+    assert!(buf.is_full());
+    let survivors: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(survivors, vec![3, 5, 7, 8, 9]);
+}
+
+// Test drain_filter on a partially filled buffer that never wrapped
+#[test]
+fn test_drain_filter_unwrapped() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=4 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    assert_eq!(buf.drain_filter(|&x| x > 10), Vec::<i32>::new());
+    assert_eq!(buf.drain_filter(|&x| x == 3), vec![3]);
+    let survivors: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(survivors, vec![2, 4]);
+}
+
+// Test from_iter_truncating keeps only the first capacity items of a range
+#[test]
+fn test_from_iter_truncating_longer_than_capacity() {
+    let mut buf = TestBuffer::from_iter_truncating(10..100);
+    assert!(buf.is_full());
+    assert_eq!(buf.len(), 5);
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![10, 11, 12, 13, 14]);
+}
+
+// Test from_iter_truncating with a short iterator and an overwrite buffer
+#[test]
+fn test_from_iter_truncating_short_and_overwrite() {
+    let buf = TestBuffer::from_iter_truncating(vec![1, 2]);
+    assert_eq!(buf.len(), 2);
+
+    let mut overwrite = OverwriteBuffer::from_iter_truncating(1..=10);
+    assert_eq!(overwrite.dequeue(), Some(1));
+}
+
+// Test most_recent returns the newest elements of a wrapped buffer oldest first
+#[test]
+fn test_most_recent_on_wrapped_buffer() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.most_recent(2), vec![6, 7]);
+    assert_eq!(buf.most_recent(4), vec![4, 5, 6, 7]);
+    assert_eq!(buf.most_recent(10), vec![3, 4, 5, 6, 7]);
+    assert!(buf.most_recent(0).is_empty());
+    assert_eq!(buf.len(), 5);
+}
+
+// Test overwrite-mode enqueue reports free space and hands back evicted values
+#[test]
+fn test_overwrite_enqueue_returns_evicted() {
+    let mut buf = OverwriteBuffer::new();
+    assert_eq!(buf.enqueue(1), Ok(None));
+    assert_eq!(buf.enqueue(2), Ok(None));
+    assert_eq!(buf.enqueue(3), Ok(None));
+    assert_eq!(buf.enqueue(4), Ok(Some(1)));
+    assert_eq!(buf.enqueue(5), Ok(Some(2)));
+    assert_eq!(buf.dequeue(), Some(3));
+    assert_eq!(buf.enqueue(6), Ok(None));
+}
+
+#[ring_buffer(2, overwrite)]
+struct PooledBuffer {
+    data: Vec<Vec<u8>>,
+}
+
+// Test an evicted allocation can be recycled by the caller
+#[test]
+fn test_overwrite_recycles_evicted_allocation() {
+    let mut buf = PooledBuffer::new();
+    let mut pool: Vec<Vec<u8>> = Vec::new();
+    for round in 0..5u8 {
+        let mut frame = pool.pop().unwrap_or_else(|| Vec::with_capacity(64));
+        frame.clear();
+        frame.push(round);
+        if let Some(evicted) = buf.enqueue(frame).unwrap() {
+            assert_eq!(evicted.capacity(), 64);
+            pool.push(evicted);
+        }
+    }
+    assert_eq!(buf.dequeue(), Some(vec![3]));
+    assert_eq!(buf.dequeue(), Some(vec![4]));
+}
+
+// Test chunks splits the logical contents into batches across the seam
+#[test]
+fn test_chunks_on_wrapped_buffer() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    let chunks: Vec<Vec<&i32>> = buf.chunks(2).collect();
+    assert_eq!(chunks, vec![vec![&3, &4], vec![&5, &6], vec![&7]]);
+    assert_eq!(buf.chunks(5).count(), 1);
+    assert_eq!(buf.len(), 5);
+}
+
+// Test chunks on an empty buffer yields nothing
+#[test]
+fn test_chunks_empty() {
+    let buf = TestBuffer::new();
+    assert_eq!(buf.chunks(3).count(), 0);
+}
+
+// Test truncate keeps the oldest elements of a wrapped buffer
+#[test]
+fn test_truncate_wrapped_buffer() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    buf.truncate(2);
+    assert_eq!(buf.len(), 2);
+    buf.enqueue(8).unwrap();
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![3, 4, 8]);
+}
+
+// Test truncate to a length at or above len is a no-op
+#[test]
+fn test_truncate_noop() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    buf.truncate(2);
+    buf.truncate(10);
+    assert_eq!(buf.len(), 2);
+    buf.truncate(0);
+    assert!(buf.is_empty());
+    buf.enqueue(3).unwrap();
+    assert_eq!(buf.dequeue(), Some(3));
+}
+
+// Test truncate drops the removed items instead of keeping them in storage
+#[test]
+fn test_truncate_drops_removed_items() {
+    let mut buf = RcBuffer::new();
+    let item = Rc::new("shared".to_string());
+    for _ in 0..5 {
+        buf.enqueue(Rc::clone(&item)).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(Rc::clone(&item)).unwrap();
+    buf.enqueue(Rc::clone(&item)).unwrap();
+    assert_eq!(Rc::strong_count(&item), 6);
+    buf.truncate(1);
+    assert_eq!(Rc::strong_count(&item), 2);
+    buf.enqueue(Rc::clone(&item)).unwrap();
+    assert_eq!(buf.len(), 2);
+}
+
+#[ring_buffer(3, hash)]
+struct HashBuffer {
+    data: Vec<i32>,
+}
+
+// Test logically equal buffers with different layouts hash identically
+#[test]
+fn test_hash_ignores_physical_layout() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    // Compares contents so the HashSet can collapse logically equal buffers
+    struct Key(HashBuffer);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.peek_n(self.0.len()) == other.0.peek_n(other.0.len())
+        }
+    }
+
+    impl Eq for Key {}
+
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    fn hash_of(buf: &HashBuffer) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        buf.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let mut straight = HashBuffer::new();
+    straight.enqueue(1).unwrap();
+    straight.enqueue(2).unwrap();
+
+    let mut wrapped = HashBuffer::new();
+    wrapped.enqueue(9).unwrap();
+    wrapped.enqueue(9).unwrap();
+    wrapped.enqueue(1).unwrap();
+    wrapped.dequeue();
+    wrapped.dequeue();
+    wrapped.enqueue(2).unwrap();
+
+    assert_eq!(hash_of(&straight), hash_of(&wrapped));
+
+    let mut set = HashSet::new();
+    set.insert(Key(straight));
+    set.insert(Key(wrapped));
+    assert_eq!(set.len(), 1);
+
+    let mut different = HashBuffer::new();
+    different.enqueue(2).unwrap();
+    different.enqueue(1).unwrap();
+    set.insert(Key(different));
+    assert_eq!(set.len(), 2);
+}
+
+#[ring_buffer(0, allow_zero)]
+struct DisabledBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(0, allow_zero, overwrite)]
+struct DisabledOverwriteBuffer {
+    data: Vec<i32>,
+}
+
+// Test a zero-capacity buffer behaves as a degenerate empty buffer
+#[test]
+fn test_zero_capacity_buffer() {
+    let mut buf = DisabledBuffer::new();
+    assert_eq!(buf.capacity(), 0);
+    assert!(buf.is_full());
+    assert!(buf.is_empty());
+    assert_eq!(buf.enqueue(1), Err(1));
+    assert!(!buf.saturating_enqueue(2));
+    assert_eq!(buf.dequeue(), None);
+    assert_eq!(buf.dequeue_if(|_| true), None);
+    assert_eq!(buf.len(), 0);
+    assert!(buf.peek_n(3).is_empty());
+    assert!(buf.drain_filter(|_| true).is_empty());
+    assert_eq!(buf.occupancy(), 1.0);
+    buf.truncate(0);
+    buf.clear();
+    assert!(buf.is_empty());
+}
+
+// Test a zero-capacity overwrite buffer rejects instead of evicting
+#[test]
+fn test_zero_capacity_overwrite_buffer() {
+    let mut buf = DisabledOverwriteBuffer::new();
+    assert_eq!(buf.enqueue(1), Err(1));
+    assert_eq!(buf.dequeue(), None);
+    assert!(buf.is_empty());
+}
+
+mod method_vis {
+    use ring_buffer_macro::ring_buffer;
+
+    #[ring_buffer(3, method_vis = "pub(crate)")]
+    pub struct CrateBuffer {
+        data: Vec<i32>,
+    }
+}
+
+// Test methods generated with method_vis are reachable from the parent module
+#[test]
+fn test_method_vis_reachable_from_parent() {
+    let mut buf = method_vis::CrateBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.dequeue(), Some(1));
+}
+
+// Test as_slices splits a wrapped buffer at the seam
+#[test]
+fn test_as_slices() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.as_slices(), (&[1, 2][..], &[][..]));
+    for i in 3..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.as_slices(), (&[3, 4, 5][..], &[6][..]));
+}
+
+// Test make_contiguous linearizes a wrapped buffer in FIFO order
+#[test]
+fn test_make_contiguous_on_wrapped_buffer() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.make_contiguous(), &[4, 5, 6, 7]);
+    assert_eq!(buf.as_slices(), (&[4, 5, 6, 7][..], &[][..]));
+    buf.enqueue(8).unwrap();
+    assert!(buf.is_full());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![4, 5, 6, 7, 8]);
+}
+
+// Test make_contiguous on a partially filled buffer that never wrapped
+#[test]
+fn test_make_contiguous_unwrapped() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=3 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    assert_eq!(buf.make_contiguous(), &[2, 3]);
+    buf.enqueue(4).unwrap();
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![2, 3, 4]);
+}
+
+// Test replace_front swaps the oldest element without changing the length
+#[test]
+fn test_replace_front() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.replace_front(20), Some(2));
+    assert_eq!(buf.len(), 5);
+    assert!(buf.is_full());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![20, 3, 4, 5, 6]);
+}
+
+// Test replace_front on an empty buffer neither inserts nor returns a value
+#[test]
+fn test_replace_front_empty() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.replace_front(1), None);
+    assert!(buf.is_empty());
+}
+
+#[ring_buffer(64, sync)]
+struct SharedBuffer {
+    data: Vec<u32>,
+}
+
+// Test the sync wrapper accepts producers on several threads
+#[test]
+fn test_sync_wrapper_multi_threaded() {
+    use std::thread;
+
+    let shared = Arc::new(SyncSharedBuffer::new());
+    let producers: Vec<_> = (0..4u32)
+        .map(|t| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for i in 0..10 {
+                    shared.enqueue(t * 100 + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    assert_eq!(shared.len(), 40);
+    let mut drained: Vec<u32> = std::iter::from_fn(|| shared.dequeue()).collect();
+    assert!(shared.is_empty());
+    drained.sort_unstable();
+    let expected: Vec<u32> = (0..4u32)
+        .flat_map(|t| (0..10).map(move |i| t * 100 + i))
+        .collect();
+    assert_eq!(drained, expected);
+}
+
+// Test the sync wrapper exposes the full API through lock
+#[test]
+fn test_sync_wrapper_lock_and_into_inner() {
+    let shared = SyncSharedBuffer::new();
+    shared.enqueue(1).unwrap();
+    shared.lock().enqueue(2).unwrap();
+    assert_eq!(shared.lock().capacity(), 64);
+    let mut inner = shared.into_inner();
+    assert_eq!(inner.dequeue(), Some(1));
+    assert_eq!(inner.dequeue(), Some(2));
+}
+
+#[ring_buffer(4, sync, overwrite)]
+struct SharedOverwriteBuffer {
+    data: Vec<u32>,
+}
+
+// Test the sync wrapper of an overwrite buffer can be shared across threads
+#[test]
+fn test_sync_wrapper_overwrite_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SyncSharedBuffer>();
+    assert_send_sync::<SyncSharedOverwriteBuffer>();
+
+    let shared = Arc::new(SyncSharedOverwriteBuffer::new());
+    let evictions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = Arc::clone(&evictions);
+    shared.lock().set_on_overflow(Box::new(move |_| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }));
+
+    let producer = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            for i in 0..6 {
+                shared.enqueue(i).unwrap();
+            }
+        })
+    };
+    producer.join().unwrap();
+    assert_eq!(shared.len(), 4);
+    assert_eq!(evictions.load(std::sync::atomic::Ordering::Relaxed), 2);
+}
+
+// Test extract_if moves out matching elements of a wrapped buffer in order
+#[test]
+fn test_extract_if_some() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.extract_if(|&x| x > 4), vec![5, 6]);
+    assert_eq!(buf.as_slices(), (&[2, 3, 4][..], &[][..]));
+}
+
+// Test extract_if removing everything empties the buffer
+#[test]
+fn test_extract_if_all() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.extract_if(|_| true), vec![2, 3, 4, 5, 6]);
+    assert!(buf.is_empty());
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.dequeue(), Some(7));
+}
+
+// Test extract_if removing nothing keeps the contents in order
+#[test]
+fn test_extract_if_none() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert!(buf.extract_if(|_| false).is_empty());
+    assert!(buf.is_full());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![2, 3, 4, 5, 6]);
+}
+
+#[ring_buffer(4)]
+struct BorrowedBuffer<'a, T: Clone + 'a> {
+    data: Vec<&'a T>,
+}
+
+#[ring_buffer(3, hash, sync)]
+struct WhereBoundBuffer<K, V>
+where
+    K: Clone + std::fmt::Debug + std::hash::Hash,
+    V: Clone + Default + std::hash::Hash,
+{
+    data: Vec<(K, V)>,
+}
+
+// Test a lifetime-parameterized struct holding borrowed elements
+#[test]
+fn test_lifetime_parameterized_buffer() {
+    let words = ["alpha".to_string(), "beta".to_string()];
+    let mut buf: BorrowedBuffer<'_, String> = BorrowedBuffer::new();
+    buf.enqueue(&words[0]).unwrap();
+    buf.enqueue(&words[1]).unwrap();
+    assert_eq!(buf.peek_n(1), vec![&&words[0]]);
+    assert_eq!(buf.dequeue(), Some(&words[0]));
+    assert_eq!(buf.dequeue(), Some(&words[1]));
+}
+
+// Test an existing multi-bound where clause is kept alongside generated bounds
+#[test]
+fn test_where_clause_buffer() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hash;
+
+    let mut buf: WhereBoundBuffer<&str, u8> = WhereBoundBuffer::new();
+    buf.enqueue(("a", 1)).unwrap();
+    buf.enqueue(("b", u8::default())).unwrap();
+    let mut hasher = DefaultHasher::new();
+    buf.hash(&mut hasher);
+    assert_eq!(buf.dequeue(), Some(("a", 1)));
+    assert_eq!(buf.dequeue_if(|(k, _)| *k == "b"), Some(("b", 0)));
+
+    let shared: SyncWhereBoundBuffer<&str, u8> = SyncWhereBoundBuffer::new();
+    shared.enqueue(("c", 2)).unwrap();
+    assert_eq!(shared.dequeue(), Some(("c", 2)));
+}
+
+// Test saturating_enqueue as a fire-and-forget statement drops rejected items
+#[test]
+fn test_saturating_enqueue_fire_and_forget() {
+    let mut buf = RcBuffer::new();
+    let item = Rc::new("payload".to_string());
+    for _ in 0..8 {
+        buf.saturating_enqueue(Rc::clone(&item));
+    }
+    assert!(buf.is_full());
+    assert_eq!(Rc::strong_count(&item), 6);
+    assert_eq!(buf.dequeue().as_deref(), Some(&"payload".to_string()));
+}
+
+// Test reset_with replaces old contents and keeps the allocation
+#[test]
+fn test_reset_with_replaces_contents() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.reset_with(&[10, 20, 30]);
+    assert_eq!(buf.len(), 3);
+    buf.enqueue(40).unwrap();
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![10, 20, 30, 40]);
+}
+
+// Test reset_with truncates a slice longer than capacity
+#[test]
+fn test_reset_with_truncates() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(99).unwrap();
+    buf.reset_with(&[1, 2, 3, 4, 5, 6, 7]);
+    assert!(buf.is_full());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![1, 2, 3, 4, 5]);
+}
+
+#[ring_buffer(4, ord)]
+struct OrdBuffer {
+    data: Vec<i32>,
+}
+
+fn ord_buffer(items: &[i32], rotate: usize) -> OrdBuffer {
+    let mut buf = OrdBuffer::new();
+    for _ in 0..rotate {
+        buf.enqueue(0).unwrap();
+        buf.dequeue();
+    }
+    for &item in items {
+        buf.enqueue(item).unwrap();
+    }
+    buf
+}
+
+// Test logical equality ignores the physical layout of the contents
+#[test]
+fn test_ord_equality_ignores_layout() {
+    assert!(ord_buffer(&[1, 2, 3], 0) == ord_buffer(&[1, 2, 3], 3));
+    assert!(ord_buffer(&[1, 2, 3], 0) != ord_buffer(&[1, 2], 3));
+    assert_eq!(ord_buffer(&[], 0).to_vec(), Vec::<i32>::new());
+}
+
+// Test sorting buffers matches sorting their to_vec outputs
+#[test]
+fn test_ord_sorting_matches_to_vec() {
+    let mut buffers = [
+        ord_buffer(&[3, 1], 1),
+        ord_buffer(&[1, 2, 3], 2),
+        ord_buffer(&[], 3),
+        ord_buffer(&[1, 2], 0),
+        ord_buffer(&[1, 2, 3, 0], 3),
+    ];
+    let mut expected: Vec<Vec<i32>> = buffers.iter().map(|b| b.to_vec()).collect();
+    expected.sort();
+    buffers.sort();
+    let sorted: Vec<Vec<i32>> = buffers.iter().map(|b| b.to_vec()).collect();
+    assert_eq!(sorted, expected);
+    assert!(ord_buffer(&[1, 2], 1) < ord_buffer(&[1, 3], 2));
+}
+
+// Test a bulk overwrite keeps the last capacity items of old and new contents
+#[test]
+fn test_enqueue_overwrite_oldest_batch() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    buf.enqueue_overwrite_oldest(vec![3, 4]);
+    assert_eq!(buf.to_vec(), vec![1, 2, 3, 4]);
+    buf.enqueue_overwrite_oldest(5..=11);
+    assert!(buf.is_full());
+    assert_eq!(buf.to_vec(), vec![7, 8, 9, 10, 11]);
+}
+
+// Test a bulk overwrite in overwrite mode reports evictions to the callback
+#[test]
+fn test_enqueue_overwrite_oldest_in_overwrite_mode() {
+    use std::sync::Mutex;
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let mut buf = OverwriteBuffer::new();
+    let sink = Arc::clone(&evicted);
+    buf.set_on_overflow(Box::new(move |item| sink.lock().unwrap().push(*item)));
+    buf.enqueue_overwrite_oldest(1..=5);
+    assert_eq!(*evicted.lock().unwrap(), vec![1, 2]);
+    assert_eq!(buf.dequeue(), Some(3));
+}
+
+#[ring_buffer(3)]
+struct SendBuffer<T: Clone + Send> {
+    data: Vec<T>,
+}
+
+#[ring_buffer(3)]
+struct WhereCloneBuffer<T>
+where
+    T: Clone + Send,
+{
+    data: Vec<T>,
+}
+
+// Test cloning methods work when the struct already bounds the element by Clone inline
+#[test]
+fn test_declared_clone_bound_inline() {
+    let mut buf: SendBuffer<String> = SendBuffer::new();
+    buf.enqueue("a".to_string()).unwrap();
+    buf.enqueue("b".to_string()).unwrap();
+    assert_eq!(buf.dequeue_if(|s| s == "a"), Some("a".to_string()));
+    assert_eq!(buf.to_vec(), vec!["b".to_string()]);
+
+    let handle = std::thread::spawn(move || buf.dequeue());
+    assert_eq!(handle.join().unwrap(), Some("b".to_string()));
+}
+
+// Test cloning methods work when the struct bounds the element by Clone in a where clause
+#[test]
+fn test_declared_clone_bound_where_clause() {
+    let mut buf: WhereCloneBuffer<Vec<u8>> = WhereCloneBuffer::new();
+    buf.enqueue(vec![1]).unwrap();
+    buf.enqueue(vec![2]).unwrap();
+    assert_eq!(buf.most_recent(1), vec![vec![2]]);
+    assert_eq!(buf.dequeue(), Some(vec![1]));
+}
+
+// Test iter_mut visits live items in FIFO order across the wraparound
+#[test]
+fn test_iter_mut_doubles_wrapped_items() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+
+    let visited: Vec<i32> = buf.iter_mut().map(|item| *item).collect();
+    assert_eq!(visited, vec![3, 4, 5, 6, 7]);
+
+    for item in buf.iter_mut() {
+        *item *= 2;
+    }
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![6, 8, 10, 12, 14]);
+    assert_eq!(buf.iter_mut().count(), 0);
+}
+
+#[derive(Debug, PartialEq)]
+struct Ticket(u32);
+
+#[ring_buffer(3)]
+struct MoveBuffer<T> {
+    data: Vec<T>,
+}
+
+// Test into_vec moves non-Clone items out in FIFO order across the wraparound
+#[test]
+fn test_into_vec_moves_without_clone() {
+    let mut buf: MoveBuffer<Ticket> = MoveBuffer::new();
+    for i in 1..=3 {
+        buf.enqueue(Ticket(i)).unwrap();
+    }
+    assert_eq!(buf.extract_if(|t| t.0 == 1), vec![Ticket(1)]);
+    buf.enqueue(Ticket(4)).unwrap();
+    assert_eq!(buf.into_vec(), vec![Ticket(2), Ticket(3), Ticket(4)]);
+}
+
+// Test into_vec on a partially drained buffer returns only the live items
+#[test]
+fn test_into_vec_partial() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=4 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    assert_eq!(buf.into_vec(), vec![2, 3, 4]);
+    assert_eq!(TestBuffer::new().into_vec(), Vec::<i32>::new());
+}
+
+// Test converting an array of exactly capacity elements yields a full buffer in order
+#[test]
+fn test_from_array() {
+    let mut buf: TestBuffer = [1, 2, 3, 4, 5].into();
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(1));
+    assert_eq!(buf.to_vec(), vec![2, 3, 4, 5]);
+
+    let tickets: MoveBuffer<Ticket> = MoveBuffer::from([Ticket(1), Ticket(2), Ticket(3)]);
+    assert_eq!(tickets.into_vec(), vec![Ticket(1), Ticket(2), Ticket(3)]);
+}
+
+// Test storage never grows past capacity over many enqueue/dequeue cycles
+#[test]
+fn test_storage_never_exceeds_capacity() {
+    let mut buf = TestBuffer::new();
+    let mut overwriting = OverwriteBuffer::new();
+    for i in 0..1000 {
+        let _ = buf.enqueue(i);
+        let _ = overwriting.enqueue(i);
+        if i % 3 == 0 {
+            buf.dequeue();
+        }
+        if i % 7 == 0 {
+            overwriting.dequeue();
+        }
+        assert!(buf.data.len() <= buf.capacity());
+        assert!(overwriting.data.len() <= overwriting.capacity());
+    }
+    buf.enqueue_overwrite_oldest(0..50);
+    assert_eq!(buf.data.len(), buf.capacity());
+}
+
+// Test try_from_iter accepts an iterator that exactly fills the buffer
+#[test]
+fn test_try_from_iter_exact_fit() {
+    let mut buf = TestBuffer::try_from_iter(1..=5).unwrap();
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(1));
+}
+
+// Test try_from_iter reports how many items an overflowing iterator attempted
+#[test]
+fn test_try_from_iter_overflow() {
+    let err = match TestBuffer::try_from_iter(1..=8) {
+        Ok(_) => panic!("expected a capacity error"),
+        Err(err) => err,
+    };
+    assert_eq!(
+        err,
+        TestBufferCapacityError::Overflow {
+            capacity: 5,
+            attempted: 8
+        }
+    );
+    assert_eq!(err.overflow(), 3);
+    assert_eq!(
+        err.to_string(),
+        "8 items do not fit in a ring buffer of capacity 5"
+    );
+}
+
+// Test position_of returns logical indices that line up with get on a wrapped buffer
+#[test]
+fn test_position_of_wrapped() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i * 10).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(60).unwrap();
+    buf.enqueue(70).unwrap();
+
+    let index = buf.position_of(|&x| x > 50).unwrap();
+    assert_eq!(index, 3);
+    assert_eq!(buf.get(index), Some(&60));
+    assert_eq!(buf.position_of(|&x| x == 10), None);
+    assert_eq!(buf.get(0), Some(&30));
+    assert_eq!(buf.get(5), None);
+
+    buf.swap(0, index);
+    assert_eq!(buf.get(0), Some(&60));
+}
+
+// Test mutations through peek_mut are visible on the next dequeue
+#[test]
+fn test_peek_mut_accumulates_front() {
+    let mut buf: MoveBuffer<Ticket> = MoveBuffer::new();
+    assert!(buf.peek_mut().is_none());
+
+    buf.enqueue(Ticket(1)).unwrap();
+    buf.enqueue(Ticket(2)).unwrap();
+    for _ in 0..3 {
+        if let Some(front) = buf.peek_mut() {
+            front.0 += 10;
+        }
+    }
+    assert_eq!(buf.into_vec(), vec![Ticket(31), Ticket(2)]);
+
+    let mut buf = TestBuffer::new();
+    buf.enqueue(5).unwrap();
+    *buf.peek_mut().unwrap() *= 3;
+    assert_eq!(buf.dequeue(), Some(15));
+}
+
+// Test append moves as many items as fit, preserving order in both buffers
+#[test]
+fn test_append_full_into_half_empty() {
+    let mut dest = TestBuffer::new();
+    dest.enqueue(1).unwrap();
+    dest.enqueue(2).unwrap();
+
+    let mut src = TestBuffer::new();
+    for i in 10..15 {
+        src.enqueue(i).unwrap();
+    }
+    src.dequeue();
+    src.enqueue(15).unwrap();
+
+    assert_eq!(dest.append(&mut src), 3);
+    assert!(dest.is_full());
+    assert_eq!(src.len(), 2);
+    assert_eq!(dest.append(&mut src), 0);
+
+    let drained: Vec<i32> = std::iter::from_fn(|| dest.dequeue()).collect();
+    assert_eq!(drained, vec![1, 2, 11, 12, 13]);
+
+    src.enqueue(16).unwrap();
+    assert_eq!(src.to_vec(), vec![14, 15, 16]);
+}
+
+// Test append moves non-Clone items
+#[test]
+fn test_append_moves_non_clone() {
+    let mut dest: MoveBuffer<Ticket> = MoveBuffer::new();
+    let mut src: MoveBuffer<Ticket> = MoveBuffer::from([Ticket(1), Ticket(2), Ticket(3)]);
+    assert_eq!(dest.append(&mut src), 3);
+    assert!(src.is_empty());
+    assert_eq!(dest.into_vec(), vec![Ticket(1), Ticket(2), Ticket(3)]);
+}
+
+#[ring_buffer(4, const_new)]
+struct ConstBuffer {
+    data: Vec<u32>,
+}
+
+const EMPTY_CONST_BUFFER: ConstBuffer = ConstBuffer::new();
+
+static SHARED_CONST_BUFFER: std::sync::Mutex<ConstBuffer> =
+    std::sync::Mutex::new(ConstBuffer::new());
+
+// Test a const-initialised buffer allocates exactly its capacity on first use
+#[test]
+fn test_const_new_in_const_binding() {
+    let mut buf = EMPTY_CONST_BUFFER;
+    assert!(buf.is_empty());
+    assert_eq!(buf.data.capacity(), 0);
+
+    for i in 1..=5 {
+        let _ = buf.enqueue(i);
+    }
+    assert_eq!(buf.data.capacity(), 4);
+    assert_eq!(buf.to_vec(), vec![1, 2, 3, 4]);
+}
+
+// Test a const-initialised buffer in a static
+#[test]
+fn test_const_new_in_static() {
+    let mut buf = SHARED_CONST_BUFFER.lock().unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.dequeue(), Some(7));
+}
+
+// Test merge fills the destination and leaves the overflow in the source, in order
+#[test]
+fn test_merge_with_overflow() {
+    let mut dest = TestBuffer::from_slice(&[1, 2, 3]);
+    let mut src = TestBuffer::from_slice(&[4, 5, 6, 7]);
+
+    dest.merge(&mut src);
+    assert_eq!(dest.to_vec(), vec![1, 2, 3, 4, 5]);
+    assert_eq!(src.to_vec(), vec![6, 7]);
+
+    dest.dequeue();
+    dest.merge(&mut src);
+    assert_eq!(dest.to_vec(), vec![2, 3, 4, 5, 6]);
+    assert_eq!(src.to_vec(), vec![7]);
+}
+
+#[ring_buffer(3, display)]
+struct DisplayBuffer {
+    data: Vec<String>,
+}
+
+#[ring_buffer(left = 2, right = 2, display)]
+struct DisplayPair {
+    left: Vec<i32>,
+    right: Vec<f32>,
+}
+
+// Test Display lists items in FIFO order after wraparound
+#[test]
+fn test_display_wrapped() {
+    let mut buf = DisplayBuffer::new();
+    assert_eq!(buf.to_string(), "[]");
+    for word in ["a", "b", "c"] {
+        buf.enqueue(word.to_string()).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue("d".to_string()).unwrap();
+    assert_eq!(buf.to_string(), "[b, c, d]");
+
+    let mut pair = DisplayPair::new();
+    pair.enqueue_left(1).unwrap();
+    pair.enqueue_right(0.5).unwrap();
+    pair.enqueue_right(2.0).unwrap();
+    assert_eq!(pair.to_string(), "[1] [0.5, 2]");
+}
+
+// Test split_index agrees with the as_slices split
+#[test]
+fn test_split_index_matches_as_slices() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.split_index(), None);
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.split_index(), None);
+
+    for _ in 0..3 {
+        buf.dequeue();
+    }
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+
+    let (front, back) = buf.as_slices();
+    assert_eq!(buf.split_index(), Some(2));
+    assert_eq!(front.len(), 2);
+    assert_eq!(back.len(), 2);
+
+    buf.make_contiguous();
+    assert_eq!(buf.split_index(), None);
+}
+
+#[ring_buffer(8)]
+struct CowBuffer<'a> {
+    data: Vec<Cow<'a, str>>,
+}
+
+// Test a lifetime-parameterised Cow element type with borrowed and owned values
+#[test]
+fn test_cow_elements() {
+    let borrowed = String::from("borrowed");
+    let mut buf = CowBuffer::new();
+    buf.enqueue(Cow::Borrowed(borrowed.as_str())).unwrap();
+    buf.enqueue(Cow::Owned("owned".to_string())).unwrap();
+    assert_eq!(buf.to_vec(), vec!["borrowed", "owned"]);
+
+    assert!(matches!(buf.dequeue(), Some(Cow::Borrowed("borrowed"))));
+    match buf.dequeue() {
+        Some(Cow::Owned(s)) => assert_eq!(s, "owned"),
+        other => panic!("expected an owned value, got {:?}", other),
+    }
+    assert_eq!(buf.dequeue(), None);
+}
+
+// Test a snapshot indexes and iterates the live items while the buffer is borrowed
+#[test]
+fn test_snapshot_view() {
+    let mut buf = TestBuffer::new();
+    assert!(buf.snapshot().is_empty());
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+
+    let view = buf.snapshot();
+    let copy = view;
+    assert_eq!(view.len(), 5);
+    assert_eq!(view.get(0), Some(&2));
+    assert_eq!(view.get(4), Some(&6));
+    assert_eq!(view.get(5), None);
+    assert_eq!(
+        copy.iter().copied().collect::<Vec<_>>(),
+        vec![2, 3, 4, 5, 6]
+    );
+    assert_eq!(buf.peek_n(1), vec![&2]);
+
+    let conn = Connection::new();
+    assert_eq!(conn.snapshot_inbound().len(), 0);
+}
+
+#[derive(RingBuffer)]
+#[ring_capacity(3)]
+struct DerivedBuffer {
+    data: Vec<i32>,
+    capacity: usize,
+    head: usize,
+    tail: usize,
+    size: usize,
+}
+
+type OverflowHook<T> = Option<Box<dyn FnMut(&T) + Send>>;
+
+#[derive(RingBuffer)]
+#[ring_capacity(jobs = 2, overwrite)]
+struct DerivedQueue<T: Clone> {
+    jobs: Vec<T>,
+    jobs_capacity: usize,
+    jobs_head: usize,
+    jobs_tail: usize,
+    jobs_size: usize,
+    jobs_on_overflow: OverflowHook<T>,
+}
+
+// Test the derive form behaves like the attribute form
+#[test]
+fn test_derive_form() {
+    let mut buf = DerivedBuffer::new();
+    for i in 1..=3 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.enqueue(4), Err(4));
+    assert_eq!(buf.dequeue(), Some(1));
+    buf.enqueue(4).unwrap();
+    assert_eq!(buf.to_vec(), vec![2, 3, 4]);
+}
+
+// Test the derive form with named rings and options
+#[test]
+fn test_derive_form_named_overwrite() {
+    let mut queue: DerivedQueue<&str> = DerivedQueue::new();
+    queue.enqueue_jobs("a").unwrap();
+    queue.enqueue_jobs("b").unwrap();
+    assert_eq!(queue.enqueue_jobs("c"), Ok(Some("a")));
+    assert_eq!(queue.to_vec_jobs(), vec!["b", "c"]);
+}
+
+// Test count_where over empty, partial and wrapped buffers
+#[test]
+fn test_count_where() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.count_where(|_| true), 0);
+
+    for i in 1..=3 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.count_where(|&x| x % 2 == 1), 2);
+
+    buf.enqueue(4).unwrap();
+    buf.enqueue(5).unwrap();
+    buf.dequeue();
+    buf.dequeue();
+    // The dequeued 1 and 2 are still in storage but must not be counted
+    assert_eq!(buf.count_where(|&x| x < 3), 0);
+
+    buf.enqueue(6).unwrap();
+    buf.enqueue(8).unwrap();
+    assert_eq!(buf.count_where(|&x| x % 2 == 0), 3);
+}
+
+// Test enqueue_ref clones borrowed items and leaves the originals usable
+#[test]
+fn test_enqueue_ref_from_references() {
+    let originals = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    let refs: Vec<&String> = originals.iter().collect();
+
+    let mut buf = GenericBuffer::<String>::new();
+    for item in &refs {
+        buf.enqueue_ref(item).unwrap();
+    }
+    assert_eq!(buf.to_vec(), originals);
+    assert_eq!(originals[0].len(), 1);
+
+    let mut small = OverwriteBuffer::new();
+    for i in [1, 2, 3, 4] {
+        small.enqueue_ref(&i).unwrap();
+    }
+    assert_eq!(small.to_vec(), vec![2, 3, 4]);
+
+    let mut full = TestBuffer::from_slice(&[1, 2, 3, 4, 5]);
+    let extra = 6;
+    assert_eq!(full.enqueue_ref(&extra), Err(()));
+    assert_eq!(extra, 6);
+}
+
+#[ring_buffer(3, hash, eq)]
+struct KeyBuffer {
+    data: Vec<u8>,
+}
+
+// Test buffers with equal live contents collapse to one HashSet entry
+#[test]
+fn test_eq_and_hash_in_hash_set() {
+    use std::collections::HashSet;
+
+    let straight = KeyBuffer::from_slice(&[1, 2, 3]);
+    let mut rotated = KeyBuffer::from_slice(&[9, 1, 2]);
+    rotated.dequeue();
+    rotated.enqueue(3).unwrap();
+    let different = KeyBuffer::from_slice(&[1, 2]);
+
+    assert!(straight == rotated);
+    let set: HashSet<KeyBuffer> = [straight, rotated, different].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&KeyBuffer::from_slice(&[1, 2])));
+}
+
+#[ring_buffer(8)]
+struct PowerOfTwoBuffer {
+    data: Vec<u32>,
+}
+
+// Test a power-of-two capacity wraps identically to any other capacity
+#[test]
+fn test_power_of_two_capacity_wraparound() {
+    let mut buf = PowerOfTwoBuffer::new();
+    let mut expected = std::collections::VecDeque::new();
+    for i in 0..100 {
+        if buf.is_full() {
+            assert_eq!(buf.dequeue(), expected.pop_front());
+        }
+        buf.enqueue(i).unwrap();
+        expected.push_back(i);
+        if i % 5 == 0 {
+            assert_eq!(buf.dequeue(), expected.pop_front());
+        }
+    }
+    assert_eq!(buf.to_vec(), Vec::from(expected));
+
+    // Shrinking to a non-power-of-two capacity keeps wrapping correctly
+    buf.resize(3);
+    for i in 100..110 {
+        buf.dequeue();
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.to_vec(), vec![107, 108, 109]);
+}
+
+// Test try_with_capacity rejects unusable capacities and honours valid ones
+#[test]
+fn test_try_with_capacity() {
+    assert_eq!(
+        TestBuffer::try_with_capacity(0).err(),
+        Some(TestBufferCapacityError::Invalid { capacity: 0 })
+    );
+    assert!(TestBuffer::try_with_capacity(usize::MAX).is_err());
+
+    let mut buf = TestBuffer::try_with_capacity(2).unwrap();
+    assert_eq!(buf.capacity(), 2);
+    assert_eq!(buf.data.capacity(), 2);
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    assert_eq!(buf.enqueue(3), Err(3));
+    assert_eq!(buf.dequeue(), Some(1));
+    buf.enqueue(3).unwrap();
+    assert_eq!(buf.to_vec(), vec![2, 3]);
+}
+
+mod widened_vis {
+    pub(crate) mod buffers {
+        use ring_buffer_macro::ring_buffer;
+
+        #[ring_buffer(2, method_vis = "pub")]
+        pub(crate) struct WidenedBuffer {
+            data: Vec<i32>,
+        }
+    }
+
+    pub(crate) mod consumer {
+        use super::buffers::WidenedBuffer;
+
+        pub(crate) fn fill() -> WidenedBuffer {
+            let mut buf = WidenedBuffer::new();
+            buf.enqueue(1).unwrap();
+            buf.enqueue(2).unwrap();
+            buf
+        }
+    }
+}
+
+// Test method_vis can widen methods beyond a pub(crate) struct's visibility
+#[test]
+fn test_method_vis_widens_crate_struct() {
+    let mut buf = widened_vis::consumer::fill();
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(1));
+}
+
+#[ring_buffer(4, spare_slots)]
+struct SpareBuffer {
+    data: Vec<String>,
+}
+
+// Test writing into spare slots and committing makes the items live in order
+#[test]
+fn test_spare_slots_write_and_commit() {
+    let mut buf = SpareBuffer::new();
+    for word in ["a", "b", "c"] {
+        buf.enqueue(word.to_string()).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue("d".to_string()).unwrap();
+
+    let spare = buf.spare_slots_mut();
+    assert_eq!(spare.len(), 2);
+    spare[0].write("e".to_string());
+    spare[1].write("f".to_string());
+    // SAFETY: both spare slots were just written
+    unsafe { buf.commit(2) };
+
+    assert!(buf.is_full());
+    assert_eq!(buf.enqueue("g".to_string()), Err("g".to_string()));
+    let drained: Vec<String> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec!["c", "d", "e", "f"]);
+
+    buf.enqueue("h".to_string()).unwrap();
+    buf.spare_slots_mut()[0].write("i".to_string());
+    // SAFETY: the first spare slot was just written
+    unsafe { buf.commit(1) };
+    assert_eq!(buf.to_vec(), vec!["h", "i"]);
+}
+
+// Test committing more than the spare slots panics
+#[test]
+#[should_panic(expected = "commit beyond the spare slots")]
+fn test_spare_slots_commit_overflow_panics() {
+    let mut buf = SpareBuffer::new();
+    assert_eq!(buf.spare_slots_mut().len(), 4);
+    // SAFETY: the bounds check panics before any slot is read
+    unsafe { buf.commit(5) };
+}
+
+#[ring_buffer(3, hash, eq)]
+struct GenericKeyBuffer<T> {
+    data: Vec<T>,
+}
+
+// Test Eq is only required of element types when it is used
+#[test]
+fn test_eq_gated_on_element_eq() {
+    use std::collections::HashSet;
+
+    // f32 is not Eq, but the logical PartialEq still applies
+    let a = GenericKeyBuffer::from_slice(&[0.5_f32, 1.5]);
+    let b = GenericKeyBuffer::from_slice(&[0.5_f32, 1.5]);
+    assert!(a == b);
+
+    let mut keys = HashSet::new();
+    keys.insert(GenericKeyBuffer::from_slice(&["x".to_string()]));
+    let mut same = GenericKeyBuffer::from_slice(&["w".to_string(), "x".to_string()]);
+    same.dequeue();
+    assert!(!keys.insert(same));
+    assert_eq!(keys.len(), 1);
+}
+
+// Test fold visits only live items, in FIFO order
+#[test]
+fn test_fold_excludes_stale_slots() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.fold(0, |acc, &x| acc + x), 0);
+
+    for i in [100, 200, 3, 4, 5] {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    // 100 and 200 remain in storage as stale slots
+    assert_eq!(buf.fold(0, |acc, &x| acc + x), 12);
+    assert_eq!(buf.fold(i32::MIN, |acc, &x| acc.max(x)), 5);
+
+    buf.enqueue(9).unwrap();
+    let order = buf.fold(Vec::new(), |mut acc, &x| {
+        acc.push(x);
+        acc
+    });
+    assert_eq!(order, vec![3, 4, 5, 9]);
+}
+
+// Test peek_pair returns the same item twice for a single element
+#[test]
+fn test_peek_pair_single() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.peek_pair(), None);
+    buf.enqueue(7).unwrap();
+    let (front, back) = buf.peek_pair().unwrap();
+    assert!(std::ptr::eq(front, back));
+    assert_eq!(*front, 7);
+}
+
+// Test peek_pair on a wrapped buffer returns the logical ends
+#[test]
+fn test_peek_pair_wrapped() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    assert_eq!(buf.peek_pair(), Some((&3, &6)));
+
+    let (oldest, newest) = buf.peek_pair().unwrap();
+    assert_eq!(newest - oldest, 3);
+}
+
+#[ring_buffer(4, index = "u8")]
+struct ByteIndexedBuffer {
+    data: Vec<u8>,
+}
+
+#[ring_buffer(left = 3, right = 200, index = "u16", hash, ord, display)]
+struct NarrowPair {
+    left: Vec<i32>,
+    right: Vec<i32>,
+}
+
+// Test a u8-indexed buffer stores narrow bookkeeping fields and behaves normally
+#[test]
+fn test_u8_index_type() {
+    let mut buf = ByteIndexedBuffer::new();
+    let capacity: u8 = buf.capacity;
+    assert_eq!(capacity, 4);
+    assert_eq!(buf.len(), 0);
+
+    for i in 0..10 {
+        if buf.is_full() {
+            buf.dequeue();
+        }
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.to_vec(), vec![6, 7, 8, 9]);
+    assert_eq!((buf.head, buf.tail, buf.size), (2u8, 2u8, 4u8));
+    assert_eq!(buf.get(3), Some(&9));
+
+    buf.resize(6);
+    buf.enqueue(10).unwrap();
+    assert_eq!(buf.to_vec(), vec![6, 7, 8, 9, 10]);
+    assert!(ByteIndexedBuffer::try_with_capacity(300).is_err());
+    assert_eq!(
+        ByteIndexedBuffer::try_with_capacity(255)
+            .unwrap()
+            .capacity(),
+        255
+    );
+}
+
+// Test a narrower index type with named rings and trait impls
+#[test]
+fn test_u16_index_type_named_rings() {
+    let mut a = NarrowPair::new();
+    let mut b = NarrowPair::new();
+    for i in 0..250 {
+        let _ = a.enqueue_right(i);
+        let _ = b.enqueue_right(i);
+    }
+    a.enqueue_left(1).unwrap();
+    b.enqueue_left(2).unwrap();
+    assert!(a < b);
+    assert_eq!(a.len_right(), 200);
+    let _: u16 = a.right_capacity;
+    assert!(a.to_string().starts_with("[1] [0, 1, 2"));
+}
+
+// Test resizing past the index type's range panics
+#[test]
+#[should_panic(expected = "capacity does not fit the index type")]
+fn test_index_type_resize_overflow_panics() {
+    ByteIndexedBuffer::new().resize(256);
+}
+
+#[derive(Debug, Clone)]
+struct DropCounter(Rc<std::cell::Cell<usize>>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+// Test clear_and_shrink runs destructors for every held element and frees storage
+#[test]
+fn test_clear_and_shrink_runs_destructors() {
+    let drops = Rc::new(std::cell::Cell::new(0));
+    let mut buf: MoveBuffer<DropCounter> = MoveBuffer::new();
+    for _ in 0..3 {
+        buf.enqueue(DropCounter(Rc::clone(&drops))).unwrap();
+    }
+    // The dequeued clone drops immediately; the stale original stays in storage
+    drop(buf.dequeue());
+    assert_eq!(drops.get(), 1);
+
+    buf.clear_and_shrink();
+    assert_eq!(drops.get(), 4);
+    assert_eq!(buf.data.capacity(), 0);
+    assert!(buf.is_empty());
+
+    buf.enqueue(DropCounter(Rc::clone(&drops))).unwrap();
+    assert_eq!(buf.len(), 1);
+}
+
+// Test clear releases held references immediately instead of leaving them in stale slots
+#[test]
+fn test_clear_releases_references() {
+    let shared = Rc::new("payload".to_string());
+    let mut buf = RcBuffer::new();
+    for _ in 0..3 {
+        buf.enqueue(Rc::clone(&shared)).unwrap();
+    }
+    drop(buf.dequeue());
+    assert_eq!(Rc::strong_count(&shared), 4);
+
+    buf.clear();
+    assert_eq!(Rc::strong_count(&shared), 1);
+    assert!(buf.is_empty());
+
+    buf.enqueue(Rc::clone(&shared)).unwrap();
+    assert_eq!(buf.dequeue().as_deref(), Some(&"payload".to_string()));
+}
+
+// Minimal `Stream`-style adapter over a buffer, built on the non-cloning dequeue
+struct TicketStream {
+    buf: MoveBuffer<Ticket>,
+}
+
+impl TicketStream {
+    fn poll_next(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Ticket>> {
+        std::task::Poll::Ready(self.buf.try_dequeue())
+    }
+}
+
+// Test a stream adapter drains non-Clone items in FIFO order across the wraparound
+#[test]
+fn test_try_dequeue_stream_adapter() {
+    let mut buf: MoveBuffer<Ticket> = MoveBuffer::new();
+    for i in 1..=3 {
+        buf.enqueue(Ticket(i)).unwrap();
+    }
+    assert_eq!(buf.try_dequeue(), Some(Ticket(1)));
+    buf.enqueue(Ticket(4)).unwrap();
+    buf.enqueue(Ticket(5)).unwrap_err();
+
+    let mut stream = TicketStream { buf };
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    let mut polled = Vec::new();
+    while let std::task::Poll::Ready(Some(ticket)) = stream.poll_next(&mut cx) {
+        polled.push(ticket);
+    }
+    assert_eq!(polled, vec![Ticket(2), Ticket(3), Ticket(4)]);
+
+    stream.buf.enqueue(Ticket(6)).unwrap();
+    stream.buf.enqueue(Ticket(7)).unwrap();
+    assert_eq!(stream.buf.into_vec(), vec![Ticket(6), Ticket(7)]);
+}
+
+// Test insert at the front, middle and back of a partially filled, wrapped buffer
+#[test]
+fn test_insert_front_middle_back() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=4 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(5).unwrap();
+    // Holds [3, 4, 5] with head at slot 2
+
+    buf.insert(0, 10).unwrap();
+    assert_eq!(buf.to_vec(), vec![10, 3, 4, 5]);
+    buf.insert(2, 20).unwrap();
+    assert_eq!(buf.to_vec(), vec![10, 3, 20, 4, 5]);
+    assert_eq!(buf.insert(5, 30), Err(30));
+
+    buf.dequeue();
+    buf.insert(4, 30).unwrap();
+    assert_eq!(buf.len(), 5);
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![3, 20, 4, 5, 30]);
+}
+
+// Test insert into an empty buffer behaves like enqueue
+#[test]
+fn test_insert_into_empty() {
+    let mut buf = TestBuffer::new();
+    buf.insert(0, 7).unwrap();
+    buf.insert(1, 8).unwrap();
+    buf.insert(0, 6).unwrap();
+    assert_eq!(buf.to_vec(), vec![6, 7, 8]);
+}
+
+// Test insert past the end panics
+#[test]
+#[should_panic]
+fn test_insert_out_of_range_panics() {
+    let mut buf = TestBuffer::new();
+    buf.enqueue(1).unwrap();
+    let _ = buf.insert(2, 2);
+}
+
+// Test remove from the front, middle and back of a wrapped buffer
+#[test]
+fn test_remove_front_middle_back() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    // Holds [3, 4, 5, 6, 7] wrapped across the end of storage
+
+    assert_eq!(buf.remove(2), Some(5));
+    assert_eq!(buf.to_vec(), vec![3, 4, 6, 7]);
+    assert_eq!(buf.remove(0), Some(3));
+    assert_eq!(buf.remove(1), Some(6));
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.to_vec(), vec![4, 7]);
+    assert_eq!(buf.remove(2), None);
+
+    // The freed space is reusable in FIFO order
+    for i in 8..=10 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(buf.is_full());
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![4, 7, 8, 9, 10]);
+}
+
+// Test remove moves items out of a buffer of non-Clone items
+#[test]
+fn test_remove_non_clone() {
+    let mut buf = MoveBuffer::new();
+    buf.enqueue(Ticket(1)).unwrap();
+    buf.enqueue(Ticket(2)).unwrap();
+    buf.enqueue(Ticket(3)).unwrap();
+    assert_eq!(buf.remove(1), Some(Ticket(2)));
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.remove(5), None);
+    assert_eq!(buf.try_dequeue(), Some(Ticket(1)));
+    assert_eq!(buf.try_dequeue(), Some(Ticket(3)));
+}
+
+// Test take_where removes the first match from the middle of a wrapped buffer
+#[test]
+fn test_take_where_middle_of_wrapped() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(8).unwrap();
+    // Holds [3, 4, 5, 6, 8] wrapped across the end of storage
+
+    assert_eq!(buf.take_where(|&x| x > 5), Some(6));
+    assert_eq!(buf.take_where(|&x| x > 10), None);
+    assert_eq!(buf.len(), 4);
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![3, 4, 5, 8]);
+}
+
+// Test try_dequeue agrees with dequeue when mixed with enqueues that wrap the ring
+#[test]
+fn test_try_dequeue_matches_reference_queue() {
+    let mut buf = TestBuffer::new();
+    let mut expected = std::collections::VecDeque::new();
+    for i in 0..200 {
+        if buf.enqueue(i).is_ok() {
+            expected.push_back(i);
+        }
+        match i % 3 {
+            0 => assert_eq!(buf.try_dequeue(), expected.pop_front()),
+            1 if i % 7 == 0 => assert_eq!(buf.dequeue(), expected.pop_front()),
+            _ => {}
+        }
+        assert_eq!(buf.to_vec(), Vec::from(expected.clone()));
+    }
+}
+
+// Test reversed_iter yields newest first, including across the wraparound
+#[test]
+fn test_reversed_iter() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3]);
+    assert_eq!(
+        buf.reversed_iter().copied().collect::<Vec<_>>(),
+        vec![3, 2, 1]
+    );
+
+    buf.enqueue(4).unwrap();
+    buf.enqueue(5).unwrap();
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(
+        buf.reversed_iter().copied().collect::<Vec<_>>(),
+        vec![7, 6, 5, 4, 3]
+    );
+    assert_eq!(TestBuffer::new().reversed_iter().count(), 0);
+}
+
+#[ring_buffer(4, unchecked)]
+struct UncheckedBuffer {
+    data: Vec<u32>,
+}
+
+// Test enqueue_unchecked behaves like enqueue when space is guaranteed
+#[test]
+fn test_enqueue_unchecked_with_space() {
+    let mut buf = UncheckedBuffer::new();
+    for round in 0..3 {
+        while !buf.is_full() {
+            // SAFETY: the loop condition guarantees a free slot
+            unsafe { buf.enqueue_unchecked(round) };
+        }
+        buf.dequeue();
+        buf.dequeue();
+    }
+    assert_eq!(buf.to_vec(), vec![2, 2]);
+}
+
+// Test debug builds catch enqueue_unchecked on a full buffer
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "enqueue_unchecked on a full buffer")]
+fn test_enqueue_unchecked_when_full_asserts() {
+    let mut buf = UncheckedBuffer::from_slice(&[1, 2, 3, 4]);
+    // SAFETY: deliberately violated to exercise the debug assertion
+    unsafe { buf.enqueue_unchecked(5) };
+}
+
+type Buf<T> = Vec<T>;
+
+#[ring_buffer(3, any_vec)]
+struct AliasedBuffer {
+    data: Buf<i32>,
+}
+
+// Test a Vec alias is accepted as storage under any_vec
+#[test]
+fn test_any_vec_alias() {
+    let mut buf = AliasedBuffer::new();
+    for i in 1..=4 {
+        let _ = buf.enqueue(i);
+    }
+    assert_eq!(buf.dequeue(), Some(1));
+    buf.enqueue(5).unwrap();
+    assert_eq!(buf.to_vec(), vec![2, 3, 5]);
+}
+
+#[ring_buffer(4, test)]
+struct SmokeTestedBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(inbound = 2, outbound = 3, test, overwrite)]
+struct SmokeTestedPair {
+    inbound: Vec<i32>,
+    outbound: Vec<String>,
+}
+
+#[ring_buffer(0, allow_zero, overwrite, test)]
+struct SmokeTestedDisabledBuffer {
+    data: Vec<i32>,
+}
+
+// Test the buffers generating smoke tests still behave normally; the generated
+// SmokeTestedBuffer_smoke_tests, SmokeTestedPair_smoke_tests and
+// SmokeTestedDisabledBuffer_smoke_tests modules run alongside
+#[test]
+fn test_smoke_tested_buffers() {
+    let mut buf = SmokeTestedBuffer::new();
+    buf.enqueue(1).unwrap();
+    assert_eq!(buf.dequeue(), Some(1));
+
+    let mut pair = SmokeTestedPair::new();
+    pair.enqueue_outbound("a".to_string()).unwrap();
+    assert_eq!(pair.len_outbound(), 1);
+    assert!(pair.is_empty_inbound());
+
+    let mut disabled = SmokeTestedDisabledBuffer::new();
+    assert_eq!(disabled.enqueue(1), Err(1));
+}
+
+// User types with the names the helper types of `RenamedHelpersBuffer` take by default
+struct RenamedHelpersBufferView;
+struct RenamedHelpersBufferCapacityError;
+struct RenamedHelpersBufferEnqueueError;
+struct SyncRenamedHelpersBuffer;
+
+#[ring_buffer(
+    3,
+    error_enum,
+    sync,
+    view_name = "HelpersView",
+    capacity_error_name = "HelpersError",
+    enqueue_error_name = "HelpersFull",
+    sync_name = "SharedHelpers"
+)]
+struct RenamedHelpersBuffer {
+    data: Vec<i32>,
+}
+
+// Test renamed helper types leave their default names free for user types
+#[test]
+fn test_renamed_helper_types_do_not_collide() {
+    let mut buf = RenamedHelpersBuffer::new();
+    buf.enqueue(1).unwrap();
+    let view: HelpersView<'_, i32> = buf.snapshot();
+    assert_eq!(view.len(), 1);
+    assert_eq!(
+        RenamedHelpersBuffer::try_with_capacity(0).err(),
+        Some(HelpersError::Invalid { capacity: 0 })
+    );
+    buf.enqueue(2).unwrap();
+    buf.enqueue(3).unwrap();
+    assert!(matches!(buf.enqueue(4), Err(HelpersFull::Full(4))));
+
+    let shared = SharedHelpers::new();
+    shared.enqueue(5).unwrap();
+    assert_eq!(shared.dequeue(), Some(5));
+    let _ = (
+        RenamedHelpersBufferView,
+        RenamedHelpersBufferCapacityError,
+        RenamedHelpersBufferEnqueueError,
+        SyncRenamedHelpersBuffer,
+    );
+}
+
+// Test clone_into replaces a populated destination and reuses its storage
+#[test]
+fn test_clone_into_reuses_destination() {
+    let mut src = TestBuffer::new();
+    for i in 1..=5 {
+        src.enqueue(i).unwrap();
+    }
+    src.dequeue();
+    src.dequeue();
+    src.enqueue(6).unwrap();
+    // Holds [3, 4, 5, 6] wrapped across the end of storage
+
+    let mut dst = TestBuffer::from_slice(&[10, 20, 30, 40, 50]);
+    src.clone_into(&mut dst);
+    assert_eq!(dst.to_vec(), vec![3, 4, 5, 6]);
+    assert_eq!(dst.to_vec(), src.to_vec());
+
+    let storage = (dst.data.as_ptr(), dst.data.capacity());
+    for i in 7..20 {
+        src.dequeue();
+        src.enqueue(i).unwrap();
+        src.clone_into(&mut dst);
+        assert_eq!(dst.to_vec(), src.to_vec());
+        assert_eq!((dst.data.as_ptr(), dst.data.capacity()), storage);
+    }
+}
+
+// Test clone_into rejects a destination with a different capacity
+#[test]
+#[should_panic(expected = "clone_into capacity mismatch")]
+fn test_clone_into_capacity_mismatch_panics() {
+    let src = TestBuffer::from_slice(&[1, 2]);
+    let mut dst = TestBuffer::try_with_capacity(3).unwrap();
+    src.clone_into(&mut dst);
+}
+
+// Test with_contents builds partial and full buffers in order
+#[test]
+fn test_with_contents() {
+    let mut buf = TestBuffer::with_contents(vec![1, 2, 3]);
+    assert_eq!(buf.len(), 3);
+    buf.enqueue(4).unwrap();
+    assert_eq!(buf.dequeue(), Some(1));
+
+    let full = TestBuffer::with_contents(vec![1, 2, 3, 4, 5]);
+    assert!(full.is_full());
+    assert_eq!(full.to_vec(), vec![1, 2, 3, 4, 5]);
+    assert!(TestBuffer::with_contents(Vec::new()).is_empty());
+}
+
+// Test with_contents panics instead of dropping items beyond the capacity
+#[test]
+#[should_panic(expected = "with_contents: 6 items exceed capacity 5")]
+fn test_with_contents_over_capacity_panics() {
+    TestBuffer::with_contents(vec![1, 2, 3, 4, 5, 6]);
+}
+
+#[rustfmt::skip]
+#[ring_buffer(3,)]
+struct TrailingCommaBuffer {
+    data: Vec<i32>,
+}
+
+#[rustfmt::skip]
+#[ring_buffer( 3 , eq , )]
+struct SpacedArgsBuffer {
+    data: Vec<i32>,
+}
+
+#[rustfmt::skip]
+#[ring_buffer(inbound = 2, outbound = 3,)]
+struct TrailingCommaPair {
+    inbound: Vec<i32>,
+    outbound: Vec<i32>,
+}
+
+// Test a trailing comma and extra whitespace in the arguments are accepted
+#[test]
+fn test_trailing_comma_and_whitespace_args() {
+    assert_eq!(TrailingCommaBuffer::new().capacity(), 3);
+    assert!(SpacedArgsBuffer::new() == SpacedArgsBuffer::new());
+    let pair = TrailingCommaPair::new();
+    assert_eq!(pair.capacity_inbound() + pair.capacity_outbound(), 5);
+}
+
+// Test physical_head and physical_tail track storage positions through wraparound
+#[test]
+fn test_physical_head_and_tail() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.physical_head(), None);
+    assert_eq!(buf.physical_tail(), None);
+
+    buf.enqueue(1).unwrap();
+    assert_eq!(
+        (buf.physical_head(), buf.physical_tail()),
+        (Some(0), Some(0))
+    );
+
+    for i in 2..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(
+        (buf.physical_head(), buf.physical_tail()),
+        (Some(0), Some(4))
+    );
+
+    // Three dequeues and two enqueues wrap the newest items to the start of storage
+    buf.dequeue();
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(
+        (buf.physical_head(), buf.physical_tail()),
+        (Some(3), Some(1))
+    );
+    assert_eq!(buf.data[3], 4);
+    assert_eq!(buf.data[1], 7);
+
+    while buf.len() > 1 {
+        buf.dequeue();
+    }
+    assert_eq!(
+        (buf.physical_head(), buf.physical_tail()),
+        (Some(1), Some(1))
+    );
+    buf.dequeue();
+    assert_eq!(buf.physical_head(), None);
+}
+
+// Test is_contiguous for empty, contiguous and wrapped buffers
+#[test]
+fn test_is_contiguous() {
+    let mut buf = TestBuffer::new();
+    assert!(buf.is_contiguous());
+
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    assert!(buf.is_contiguous());
+
+    buf.dequeue();
+    buf.dequeue();
+    assert!(buf.is_contiguous());
+    buf.enqueue(6).unwrap();
+    assert!(!buf.is_contiguous());
+    assert!(!buf.as_slices().1.is_empty());
+
+    buf.make_contiguous();
+    assert!(buf.is_contiguous());
+    buf.clear();
+    assert!(buf.is_contiguous());
+}
+
+// Test make_contiguous on a wrapped buffer returns a slice that writes through
+#[test]
+fn test_make_contiguous_mutable_slice() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert!(!buf.is_contiguous());
+
+    let slice = buf.make_contiguous();
+    assert_eq!(slice, &[3, 4, 5, 6, 7]);
+    slice[0] = 30;
+    slice.reverse();
+    assert!(buf.is_contiguous());
+
+    buf.enqueue(8).unwrap_err();
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![7, 6, 5, 4, 30]);
+}
+
+// Test drain_rev yields newest first and leaves the buffer empty
+#[test]
+fn test_drain_rev() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3]);
+    assert_eq!(buf.drain_rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    assert!(buf.is_empty());
+
+    // Non-Clone items, with the iterator dropped after a partial drain
+    let mut moved: MoveBuffer<Ticket> = MoveBuffer::new();
+    for i in 1..=3 {
+        moved.enqueue(Ticket(i)).unwrap();
+    }
+    moved.try_dequeue();
+    moved.enqueue(Ticket(4)).unwrap();
+    assert_eq!(moved.drain_rev().next(), Some(Ticket(4)));
+    assert!(moved.is_empty());
+    moved.enqueue(Ticket(5)).unwrap();
+    assert_eq!(moved.try_dequeue(), Some(Ticket(5)));
+}
+
+#[ring_buffer(2, on_full = "grow", max = 6)]
+struct GrowingBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(2, on_full = "grow", index = "u8", error_enum)]
+struct UnboundedGrowingBuffer {
+    data: Vec<u16>,
+}
+
+// Test try_with_capacity rejects a capacity beyond max in grow mode
+#[test]
+fn test_try_with_capacity_respects_max() {
+    assert_eq!(
+        GrowingBuffer::try_with_capacity(7).err(),
+        Some(GrowingBufferCapacityError::Invalid { capacity: 7 })
+    );
+    let buf = GrowingBuffer::try_with_capacity(6).unwrap();
+    assert_eq!(buf.capacity(), 6);
+}
+
+// Test on_full = "grow" doubles the capacity until it reaches max
+#[test]
+fn test_grow_stops_at_max() {
+    let mut buf = GrowingBuffer::new();
+    let mut capacities = Vec::new();
+    for i in 0..6 {
+        buf.enqueue(i).unwrap();
+        capacities.push(buf.capacity());
+    }
+    assert_eq!(capacities, vec![2, 2, 4, 4, 6, 6]);
+    assert!(buf.is_full());
+    assert_eq!(buf.enqueue(6), Err(6));
+    assert_eq!(buf.capacity(), 6);
+    assert_eq!(buf.to_vec(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+// Test growing a wrapped buffer keeps the items in FIFO order
+#[test]
+fn test_grow_preserves_order_across_reallocation() {
+    let mut buf = GrowingBuffer::new();
+    buf.enqueue(1).unwrap();
+    buf.enqueue(2).unwrap();
+    buf.dequeue();
+    buf.enqueue(3).unwrap();
+    // Holds [2, 3] with 3 wrapped to the start of storage
+    assert!(!buf.is_contiguous());
+
+    buf.enqueue(4).unwrap();
+    buf.enqueue(5).unwrap();
+    assert_eq!(buf.capacity(), 4);
+    assert_eq!(buf.dequeue(), Some(2));
+    buf.enqueue(6).unwrap();
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.capacity(), 6);
+    let drained: Vec<i32> = std::iter::from_fn(|| buf.dequeue()).collect();
+    assert_eq!(drained, vec![3, 4, 5, 6, 7]);
+}
+
+// Test growth without max is bounded by a narrow index type
+#[test]
+fn test_grow_bounded_by_index_type() {
+    let mut buf = UnboundedGrowingBuffer::new();
+    for i in 0..255 {
+        buf.enqueue(i).unwrap();
+    }
+    assert_eq!(buf.capacity(), 255);
+    assert_eq!(
+        buf.enqueue(255),
+        Err(UnboundedGrowingBufferEnqueueError::Full(255))
+    );
+    assert_eq!(buf.get(254), Some(&254));
+}
+
+// Test count_matching counts even items and skips stale slots left by dequeues
+#[test]
+fn test_count_matching_even() {
+    let mut buf = TestBuffer::from_slice(&[2, 4, 5, 6, 7]);
+    assert_eq!(buf.count_matching(|&x| x % 2 == 0), 3);
+
+    buf.dequeue();
+    buf.dequeue();
+    // 2 and 4 remain in storage until overwritten
+    assert_eq!(buf.count_matching(|&x| x % 2 == 0), 1);
+    buf.enqueue(9).unwrap();
+    assert_eq!(buf.count_matching(|&x| x % 2 == 0), 1);
+    assert_eq!(
+        buf.count_matching(|&x| x % 2 == 0),
+        buf.count_where(|&x| x % 2 == 0)
+    );
+}
+
+// Test generic and lifetime-parameterised buffers declared inside a function body
+#[test]
+fn test_local_generic_struct() {
+    #[ring_buffer(3)]
+    struct LocalPairs<K: Clone, V>
+    where
+        V: Clone + Default,
+    {
+        data: Vec<(K, V)>,
+    }
+
+    #[ring_buffer(2, eq)]
+    struct LocalBorrowed<'a, K: ?Sized, V: Clone> {
+        data: Vec<(&'a K, V)>,
+    }
+
+    let mut pairs: LocalPairs<&str, u32> = LocalPairs::new();
+    pairs.enqueue(("a", 1)).unwrap();
+    pairs.enqueue(("b", u32::default())).unwrap();
+    assert_eq!(pairs.dequeue(), Some(("a", 1)));
+    assert_eq!(pairs.to_vec(), vec![("b", 0)]);
+
+    let owned = String::from("key");
+    let mut borrowed: LocalBorrowed<'_, str, Vec<u8>> = LocalBorrowed::new();
+    borrowed.enqueue((owned.as_str(), vec![1])).unwrap();
+    borrowed.enqueue((&owned[1..], vec![2])).unwrap();
+    assert!(borrowed.enqueue((&owned[2..], vec![3])).is_err());
+    assert_eq!(borrowed.dequeue(), Some(("key", vec![1])));
+    assert_eq!(borrowed.get(0), Some(&("ey", vec![2])));
+    assert!(borrowed == LocalBorrowed::from_slice(&[("ey", vec![2])]));
+}
+
+#[ring_buffer(16, max_elem_size = 8)]
+struct BudgetBuffer {
+    data: Vec<u64>,
+}
+
+#[ring_buffer(4, max_elem_size = 16, const_new)]
+struct BudgetGenericBuffer<'a, T: Clone> {
+    data: Vec<&'a T>,
+}
+
+// Test element types within max_elem_size compile and behave normally
+#[test]
+fn test_max_elem_size_within_budget() {
+    let mut buf = BudgetBuffer::new();
+    buf.enqueue(u64::MAX).unwrap();
+    assert_eq!(buf.dequeue(), Some(u64::MAX));
+
+    let value = String::from("x");
+    let mut generic: BudgetGenericBuffer<'_, String> = BudgetGenericBuffer::new();
+    generic.enqueue(&value).unwrap();
+    assert_eq!(generic.dequeue(), Some(&value));
+}
+
+// Test try_extend accepts an iterator that fits, including one that exactly fills the buffer
+#[test]
+fn test_try_extend_fits() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.try_extend(vec![1, 2]).map_err(|(n, _)| n), Ok(()));
+    assert_eq!(buf.try_extend(3..=5).map_err(|(n, _)| n), Ok(()));
+    assert!(buf.is_full());
+    assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5]);
+}
+
+// Test try_extend stops when full and hands back the items that did not fit
+#[test]
+fn test_try_extend_overflow() {
+    let mut buf = TestBuffer::from_slice(&[1, 2]);
+    let (inserted, rest) = buf.try_extend(vec![3, 4, 5, 6, 7]).unwrap_err();
+    assert_eq!(inserted, 3);
+    assert_eq!(rest.collect::<Vec<_>>(), vec![6, 7]);
+    assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5]);
+
+    let (inserted, mut rest) = buf.try_extend(std::iter::repeat(0)).unwrap_err();
+    assert_eq!(inserted, 0);
+    assert_eq!(rest.next(), Some(0));
+}
+
+// Test logical_to_physical agrees with (head + i) % capacity through repeated wraparound
+#[test]
+fn test_logical_to_physical() {
+    let mut buf = TestBuffer::new();
+    assert_eq!(buf.logical_to_physical(0), None);
+
+    for step in 0..12 {
+        if buf.is_full() || step % 3 == 0 {
+            buf.dequeue();
+        }
+        buf.enqueue(step).unwrap();
+
+        for i in 0..buf.len() {
+            let physical = buf.logical_to_physical(i).unwrap();
+            assert_eq!(physical, (buf.head + i) % buf.capacity);
+            assert_eq!(buf.data[physical], *buf.get(i).unwrap());
+        }
+        assert_eq!(buf.logical_to_physical(buf.len()), None);
+    }
+}
+
+#[ring_buffer(3, vecdeque_api)]
+struct DequeLikeBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(left = 2, right = 2, vecdeque_api, overwrite)]
+struct DequeLikePair {
+    left: Vec<i32>,
+    right: Vec<i32>,
+}
+
+// Test push_back and pop_front match enqueue and dequeue step for step
+#[test]
+fn test_vecdeque_api_aliases() {
+    let mut aliased = DequeLikeBuffer::new();
+    let mut original = DequeLikeBuffer::new();
+    for i in 0..10 {
+        assert_eq!(aliased.push_back(i), original.enqueue(i));
+        if i % 2 == 0 {
+            assert_eq!(aliased.pop_front(), original.dequeue());
+        }
+        assert_eq!(aliased.to_vec(), original.to_vec());
+    }
+    assert_eq!(aliased.push_back(10), Err(10));
+
+    let mut pair = DequeLikePair::new();
+    pair.push_back_left(1).unwrap();
+    pair.push_back_left(2).unwrap();
+    assert_eq!(pair.push_back_left(3), Ok(Some(1)));
+    assert_eq!(pair.pop_front_left(), Some(2));
+    assert_eq!(pair.pop_front_right(), None);
+}
+
+// Test clone_contents_into reuses the caller's Vec without leaving stale items behind
+#[test]
+fn test_clone_contents_into_reuses_vec() {
+    let mut buf = TestBuffer::from_slice(&[1, 2, 3, 4, 5]);
+    let mut out = Vec::with_capacity(8);
+    let storage = out.as_ptr();
+
+    buf.clone_contents_into(&mut out);
+    assert_eq!(out, vec![1, 2, 3, 4, 5]);
+
+    // Wrap the buffer and shrink it below the previous snapshot
+    buf.dequeue();
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    buf.clone_contents_into(&mut out);
+    assert_eq!(out, vec![4, 5, 6]);
+    assert_eq!(out.as_ptr(), storage);
+    assert_eq!(out.capacity(), 8);
+}
+
+#[ring_buffer]
+struct InferredBuffer {
+    data: [i32; 8],
+}
+
+#[ring_buffer(4, eq)]
+struct ArrayMatchingBuffer {
+    data: [String; 4],
+}
+
+#[ring_buffer(inbound = 2, outbound = 3)]
+struct ArrayPair {
+    inbound: [u8; 2],
+    outbound: Vec<u8>,
+}
+
+// Test the capacity is inferred from an array-typed data field
+#[test]
+fn test_capacity_inferred_from_array() {
+    let mut buf = InferredBuffer::new();
+    assert_eq!(buf.capacity(), 8);
+    for i in 0..10 {
+        let _ = buf.enqueue(i);
+    }
+    assert!(buf.is_full());
+    assert_eq!(buf.dequeue(), Some(0));
+    let storage: &Vec<i32> = &buf.data;
+    assert_eq!(storage.len(), 8);
+
+    let buf: InferredBuffer = [1, 2, 3, 4, 5, 6, 7, 8].into();
+    assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+    let mut matching = ArrayMatchingBuffer::new();
+    matching.enqueue("a".to_string()).unwrap();
+    assert_eq!(matching.capacity(), 4);
+
+    let pair = ArrayPair::new();
+    assert_eq!((pair.capacity_inbound(), pair.capacity_outbound()), (2, 3));
+}
+
+// Test dequeue_into with slices smaller than, equal to and larger than the length
+#[test]
+fn test_dequeue_into_slices() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+    // Holds [2, 3, 4, 5, 6] wrapped across the end of storage
+
+    let mut small = [0; 2];
+    assert_eq!(buf.dequeue_into(&mut small), 2);
+    assert_eq!(small, [2, 3]);
+    assert_eq!(buf.len(), 3);
+
+    let mut exact = [0; 3];
+    assert_eq!(buf.dequeue_into(&mut exact), 3);
+    assert_eq!(exact, [4, 5, 6]);
+    assert!(buf.is_empty());
+
+    buf.enqueue(7).unwrap();
+    buf.enqueue(8).unwrap();
+    let mut large = [-1; 4];
+    assert_eq!(buf.dequeue_into(&mut large), 2);
+    assert_eq!(large, [7, 8, -1, -1]);
+    assert_eq!(buf.dequeue_into(&mut large), 0);
+
+    buf.enqueue(9).unwrap();
+    assert_eq!(buf.dequeue(), Some(9));
+}
+
+#[ring_buffer(3)]
+struct NamedBuffer {
+    name: String,
+    data: Vec<i32>,
+    dropped: usize,
+}
+
+#[derive(RingBuffer)]
+#[ring_capacity(2)]
+struct DerivedNamedBuffer {
+    data: Vec<i32>,
+    capacity: usize,
+    head: usize,
+    tail: usize,
+    size: usize,
+    label: Option<&'static str>,
+}
+
+// Test fields beyond the ring's own start at their Default values
+#[test]
+fn test_extra_fields_default_initialised() {
+    let mut buf = NamedBuffer::new();
+    assert_eq!(buf.name, "");
+    assert_eq!(buf.dropped, 0);
+
+    buf.name = "metrics".to_string();
+    for i in 0..5 {
+        if buf.enqueue(i).is_err() {
+            buf.dropped += 1;
+        }
+    }
+    assert_eq!(buf.to_vec(), vec![0, 1, 2]);
+    assert_eq!((buf.name.as_str(), buf.dropped), ("metrics", 2));
+
+    let mut derived = DerivedNamedBuffer::new();
+    assert_eq!(derived.label, None);
+    derived.label = Some("derived");
+    derived.enqueue(1).unwrap();
+    assert_eq!(derived.dequeue(), Some(1));
+}
+
+#[ring_buffer(3, hash, ord)]
+struct LabelledBuffer {
+    label: u32,
+    data: Vec<i32>,
+}
+
+// Test eq, hash and ord take user fields into account, not only the items
+#[test]
+fn test_user_fields_in_eq_hash_ord() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let hash_of = |buf: &LabelledBuffer| {
+        let mut hasher = DefaultHasher::new();
+        buf.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let mut a = LabelledBuffer::new();
+    let mut b = LabelledBuffer::new();
+    for i in 1..=2 {
+        a.enqueue(i).unwrap();
+        b.enqueue(i).unwrap();
+    }
+    assert!(a == b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+
+    b.label = 1;
+    assert!(a != b);
+    assert_ne!(hash_of(&a), hash_of(&b));
+    assert!(a < b);
+
+    // Items are still compared before user fields
+    a.label = 2;
+    a.dequeue();
+    assert!(a > b);
+}
+
+// Test clear_with hands every item to the closure in FIFO order and empties the buffer
+#[test]
+fn test_clear_with_collects_in_fifo_order() {
+    let mut buf = TestBuffer::new();
+    for i in 1..=5 {
+        buf.enqueue(i).unwrap();
+    }
+    buf.dequeue();
+    buf.dequeue();
+    buf.enqueue(6).unwrap();
+
+    let mut released = Vec::new();
+    buf.clear_with(|item| released.push(item));
+    assert_eq!(released, vec![3, 4, 5, 6]);
+    assert!(buf.is_empty());
+    buf.enqueue(7).unwrap();
+    assert_eq!(buf.to_vec(), vec![7]);
+
+    // Non-Clone items are moved into the closure
+    let mut moved: MoveBuffer<Ticket> = MoveBuffer::new();
+    moved.enqueue(Ticket(1)).unwrap();
+    moved.enqueue(Ticket(2)).unwrap();
+    let mut pool = Vec::new();
+    moved.clear_with(|ticket| pool.push(ticket));
+    assert_eq!(pool, vec![Ticket(1), Ticket(2)]);
+    assert!(moved.is_empty());
+}
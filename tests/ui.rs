@@ -0,0 +1,7 @@
+// Compile tests checking the macro's diagnostics and generated documentation
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/pass/*.rs");
+}
@@ -0,0 +1,25 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(5)]
+struct Conflicting {
+    data: [i32; 8],
+}
+
+const LEN: usize = 4;
+
+#[ring_buffer]
+struct NotLiteral {
+    data: [i32; LEN],
+}
+
+#[ring_buffer]
+struct NoCapacity {
+    data: Vec<i32>,
+}
+
+#[ring_buffer]
+struct ZeroLength {
+    data: [i32; 0],
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(4, const_new)]
+struct Buffer {
+    data: Vec<i32>,
+    name: String,
+}
+
+fn main() {}
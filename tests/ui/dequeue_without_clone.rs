@@ -0,0 +1,14 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(3)]
+struct Buf<T> {
+    data: Vec<T>,
+}
+
+struct Ticket;
+
+fn main() {
+    let mut buf = Buf::new();
+    let _ = buf.enqueue(Ticket);
+    let _ = buf.dequeue();
+}
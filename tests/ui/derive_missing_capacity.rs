@@ -0,0 +1,12 @@
+use ring_buffer_macro::RingBuffer;
+
+#[derive(RingBuffer)]
+struct Buffer {
+    data: Vec<i32>,
+    capacity: usize,
+    head: usize,
+    tail: usize,
+    size: usize,
+}
+
+fn main() {}
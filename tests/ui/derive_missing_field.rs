@@ -0,0 +1,12 @@
+use ring_buffer_macro::RingBuffer;
+
+#[derive(RingBuffer)]
+#[ring_capacity(4)]
+struct Buffer {
+    data: Vec<i32>,
+    capacity: usize,
+    head: usize,
+    size: usize,
+}
+
+fn main() {}
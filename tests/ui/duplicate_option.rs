@@ -0,0 +1,13 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(3, overwrite, overwrite)]
+struct DuplicateFlagBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(3, index = "u8", index = "u16")]
+struct DuplicateIndexBuffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(5)]
+enum EnumBuffer {
+    Data(Vec<i32>),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(5)]
+enum State {
+    Idle,
+    Buffering { data: Vec<i32> },
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ring_buffer_macro::ring_buffer;
+
+struct Handle(u32);
+
+#[ring_buffer(4)]
+struct Buffer {
+    data: Vec<i32>,
+    handle: Handle,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(3, view_name = "Buffer View")]
+struct SpacedViewBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(3, capacity_error_name = "type")]
+struct KeywordErrorBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(3, error_enum, enqueue_error_name = "")]
+struct EmptyEnqueueErrorBuffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(3, sync, sync_name = "Sync::Buffer")]
+struct PathSyncBuffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
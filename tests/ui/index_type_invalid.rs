@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(4, index = "i32")]
+struct Buffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(256, index = "u8")]
+struct Buffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(256, max_elem_size = 8)]
+struct Samples {
+    data: Vec<[u8; 16]>,
+}
+
+#[ring_buffer(4, max_elem_size = 0)]
+struct Empty {
+    data: Vec<()>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(3, method_vis = "crate-wide")]
+struct InvalidVisBuffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+mod inner {
+    use ring_buffer_macro::ring_buffer;
+
+    #[ring_buffer(3, method_vis = "pub(self)")]
+    pub struct SelfBuffer {
+        data: Vec<i32>,
+    }
+}
+
+fn main() {
+    let _ = inner::SelfBuffer::new();
+}
@@ -0,0 +1,18 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(4, on_full = "block")]
+struct Buffer {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(4, max = 8)]
+struct Capped {
+    data: Vec<i32>,
+}
+
+#[ring_buffer(4, on_full = "grow", max = 2)]
+struct BelowCapacity {
+    data: Vec<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(18446744073709551615)]
+struct MaxBuffer {
+    data: Vec<u8>,
+}
+
+#[ring_buffer(100000000000000000000000)]
+struct OverflowBuffer {
+    data: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(4611686018427387904)]
+struct HugeBuffer {
+    data: Vec<i32>,
+}
+
+fn main() {}
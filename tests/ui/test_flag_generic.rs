@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(3, test)]
+struct Buffer<T: Clone + Default> {
+    data: Vec<T>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(5)]
+union UnionBuffer {
+    data: u32,
+}
+
+fn main() {}
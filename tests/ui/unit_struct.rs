@@ -0,0 +1,6 @@
+use ring_buffer_macro::ring_buffer;
+
+#[ring_buffer(5)]
+struct UnitBuffer;
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ring_buffer_macro::ring_buffer;
+
+type Buf<T> = Vec<T>;
+
+#[ring_buffer(3)]
+struct Buffer {
+    data: Buf<i32>,
+}
+
+fn main() {}